@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The subdirectory of `Cache::persist_to` pages are written to, so
+/// clearing the cache never touches files it didn't write itself.
+const CACHE_DIR: &str = "lodestone-cache";
+
+/// The longest name `file_name` gives a file, which leaves room for the
+/// suffixes of fixtures and temporary files within the 255 bytes most
+/// file systems allow.
+const MAX_FILE_NAME: usize = 200;
+
+/// Tells apart the temporary files of pages being written at the same time.
+static TEMP_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// The kinds of pages the Lodestone serves, which each stay
+/// up to date for a different amount of time.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum PageKind {
+    /// The main page of a character.
+    Profile,
+    /// The `class_job` page of a character.
+    ClassJob,
+    /// A page of search results.
+    Search,
+    /// The world status page.
+    WorldStatus,
+    /// Pages of the news.
+    News,
+    /// Images, such as portraits and avatars.
+    Image,
+    /// Any other page.
+    Other,
+}
+
+impl PageKind {
+    /// Tells which kind of page a Lodestone URL points to.
+    pub fn of(url: &str) -> Self {
+        let path = url.split("/lodestone/").nth(1).unwrap_or_default();
+        let file = url.split('?').next().unwrap_or_default();
+
+        if file.ends_with(".jpg") || file.ends_with(".png") {
+            PageKind::Image
+        } else if path.starts_with("character/?") {
+            PageKind::Search
+        } else if path.starts_with("character/") && path.contains("/class_job/") {
+            PageKind::ClassJob
+        } else if path.starts_with("character/") {
+            PageKind::Profile
+        } else if path.starts_with("worldstatus") {
+            PageKind::WorldStatus
+        } else if path.starts_with("news/") {
+            PageKind::News
+        } else {
+            PageKind::Other
+        }
+    }
+
+    /// How long pages of this kind are kept by default.
+    fn default_ttl(self) -> Duration {
+        match self {
+            PageKind::Profile | PageKind::ClassJob => Duration::from_secs(30 * 60),
+            PageKind::Search | PageKind::News | PageKind::Other => Duration::from_secs(10 * 60),
+            PageKind::WorldStatus => Duration::from_secs(60),
+            PageKind::Image => Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// An opt-in cache of the pages fetched by a `Lodestone` client.
+///
+/// Pages are kept in memory up to a number of entries, dropping the least
+/// recently used ones first, and can also be written to a directory so
+/// they are still around after the process restarts.
+#[derive(Debug)]
+pub struct Cache {
+    capacity: usize,
+    ttls: HashMap<PageKind, Duration>,
+    dir: Option<PathBuf>,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    pages: HashMap<String, Entry>,
+    /// Incremented on every use, to know which entry was used least recently.
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    body: Vec<u8>,
+    fetched: SystemTime,
+    used: u64,
+}
+
+impl Cache {
+    /// Creates a cache holding up to `capacity` pages in memory.
+    pub fn new(capacity: usize) -> Self {
+        Cache {
+            capacity: capacity.max(1),
+            ttls: HashMap::new(),
+            dir: None,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// How long pages of a given kind are served from the cache.
+    ///
+    /// Defaults to 30 minutes for characters, 1 minute for the world
+    /// status, a day for images and 10 minutes for everything else.
+    pub fn ttl(mut self, kind: PageKind, ttl: Duration) -> Self {
+        self.ttls.insert(kind, ttl);
+        self
+    }
+
+    /// Also stores pages in the given directory, one file per URL,
+    /// within a `lodestone-cache` subdirectory owned by the cache.
+    ///
+    /// Writing to the directory is best effort: pages which can't be
+    /// written or read back are simply fetched again.
+    pub fn persist_to<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dir = Some(dir.as_ref().join(CACHE_DIR));
+        self
+    }
+
+    /// Removes every page from memory and from disk.
+    ///
+    /// Only the files written by the cache are removed, along with
+    /// its subdirectory if nothing else was put in there.
+    pub fn clear(&self) {
+        self.lock().pages.clear();
+
+        let dir = match &self.dir {
+            Some(dir) => dir,
+            None => return,
+        };
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.flatten() {
+            if entry.file_type().is_ok_and(|kind| kind.is_file()) && is_file_name(&entry.file_name().to_string_lossy()) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+        let _ = fs::remove_dir(dir);
+    }
+
+    /// Gets a page if it is cached and has not expired yet.
+    pub(crate) fn get(&self, url: &str) -> Option<Vec<u8>> {
+        let ttl = self.ttl_of(url);
+        let mut entries = self.lock();
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        if let Some(entry) = entries.pages.get_mut(url) {
+            if is_fresh(entry.fetched, ttl) {
+                entry.used = clock;
+                return Some(entry.body.clone());
+            }
+        }
+
+        let (fetched, body) = self.read(url)?;
+        if !is_fresh(fetched, ttl) {
+            return None;
+        }
+
+        Self::insert(&mut entries, self.capacity, url, body.clone(), fetched);
+        Some(body)
+    }
+
+    /// Stores a freshly fetched page.
+    pub(crate) fn put(&self, url: &str, body: &[u8]) {
+        let fetched = SystemTime::now();
+
+        self.write(url, body, fetched);
+        Self::insert(&mut self.lock(), self.capacity, url, body.to_vec(), fetched);
+    }
+
+    fn insert(entries: &mut Entries, capacity: usize, url: &str, body: Vec<u8>, fetched: SystemTime) {
+        entries.clock += 1;
+        let used = entries.clock;
+        entries.pages.insert(url.to_string(), Entry { body, fetched, used });
+
+        while entries.pages.len() > capacity {
+            let oldest = entries.pages
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(url, _)| url.clone());
+
+            match oldest {
+                Some(url) => entries.pages.remove(&url),
+                None => break,
+            };
+        }
+    }
+
+    fn ttl_of(&self, url: &str) -> Duration {
+        let kind = PageKind::of(url);
+        self.ttls.get(&kind).copied().unwrap_or_else(|| kind.default_ttl())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Reads a page from disk, which is stored as the time it was fetched
+    /// and the length of the body on the first line, followed by the body.
+    ///
+    /// Bodies which don't have the length they were written with are misses.
+    fn read(&self, url: &str) -> Option<(SystemTime, Vec<u8>)> {
+        let contents = fs::read(self.path(url)?).ok()?;
+        let (header, body) = split_first_line(&contents)?;
+        let (fetched, length) = header.trim().split_once(' ')?;
+
+        if length.parse::<usize>().ok()? != body.len() {
+            return None;
+        }
+        let fetched = UNIX_EPOCH + Duration::from_secs(fetched.parse().ok()?);
+
+        Some((fetched, body.to_vec()))
+    }
+
+    /// Writes a page to a temporary file before moving it into place, so
+    /// that a page is never read half-written, even if the process dies
+    /// or another thread writes the same page at the same time.
+    fn write(&self, url: &str, body: &[u8], fetched: SystemTime) {
+        let path = match self.path(url) {
+            Some(path) => path,
+            None => return,
+        };
+        let fetched = fetched.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+
+        let mut temp = path.clone().into_os_string();
+        temp.push(format!(".{}-{}.tmp", std::process::id(), TEMP_FILES.fetch_add(1, Ordering::Relaxed)));
+
+        let header = format!("{} {}\n", fetched, body.len());
+        if fs::write(&temp, [header.as_bytes(), body].concat()).is_err() || fs::rename(&temp, &path).is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+    }
+
+    fn path(&self, url: &str) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(file_name(url)))
+    }
+}
+
+/// Splits a file into its first line, and the raw bytes after it.
+pub(crate) fn split_first_line(contents: &[u8]) -> Option<(&str, &[u8])> {
+    let newline = contents.iter().position(|&b| b == b'\n')?;
+    let line = std::str::from_utf8(&contents[..newline]).ok()?;
+
+    Some((line, &contents[newline + 1..]))
+}
+
+/// Whether a file name could have been made by `file_name`,
+/// including the temporary files pages are first written to.
+fn is_file_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'%'))
+}
+
+fn is_fresh(fetched: SystemTime, ttl: Duration) -> bool {
+    fetched.elapsed().is_ok_and(|age| age < ttl)
+}
+
+/// Turns a URL into a file name, escaping anything that isn't
+/// safe to use in a path the same way URLs escape characters.
+///
+/// Names which would be too long are cut short, and end with
+/// a hash of the whole URL to still tell them apart.
+pub(crate) fn file_name(url: &str) -> String {
+    let path = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+
+    let name = path
+        .bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect::<String>();
+
+    match name.len() > MAX_FILE_NAME {
+        //  Escaped names are ASCII, so they can be cut anywhere
+        true => format!("{}_{:016x}", &name[..MAX_FILE_NAME - 17], fnv1a(url.as_bytes())),
+        false => name,
+    }
+}
+
+/// A 64-bit FNV-1a hash, which unlike the standard library's hashers
+/// is the same across runs and Rust versions, as file names must be.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
@@ -0,0 +1,641 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+
+use crate::cache::Cache;
+use crate::error::LodestoneError;
+use crate::model::domain::Domain;
+use crate::model::maintenance::{Maintenance, MaintenanceNotice};
+use crate::model::profile::Profile;
+use crate::model::world_status::WorldStatus;
+use crate::model::util::profile_url;
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::retry::RetryPolicy;
+use crate::search::{SearchBuilder, SearchIter, SearchPage, SearchResult};
+
+/// How many profiles are fetched at once by default by `Lodestone::profiles`.
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// How far back to look in the news for maintenance which
+/// hasn't ended yet. Maintenance is announced days ahead, and
+/// never lasts long, so older posts are not worth reading.
+const MAINTENANCE_LOOKBACK: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// A boxed future, as returned by `AsyncTransport`.
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The raw result of a request made by a `Transport`.
+///
+/// Pages have a text body, while images are kept as bytes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Response<B = String> {
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// The body of the response.
+    pub body: B,
+    /// How long the server asked to wait before retrying, from the `Retry-After` header.
+    pub retry_after: Option<Duration>,
+}
+
+impl<B> Response<B> {
+    /// Sets how long the server asked to wait before retrying.
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+}
+
+impl Response {
+    pub fn new<S: Into<String>>(status: u16, body: S) -> Self {
+        Response {
+            status,
+            body: body.into(),
+            retry_after: None,
+        }
+    }
+
+    /// Turns the body of a page into bytes, the same as an image.
+    pub fn into_bytes(self) -> Response<Vec<u8>> {
+        Response {
+            status: self.status,
+            body: self.body.into_bytes(),
+            retry_after: self.retry_after,
+        }
+    }
+}
+
+/// Reads a `Retry-After` header given in seconds. Dates are not
+/// supported, as the Lodestone doesn't use them.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Performs the HTTP requests for a `Lodestone` client.
+///
+/// Implementing this makes it possible to change how pages are fetched,
+/// or to serve canned HTML in tests without touching the network.
+pub trait Transport: Send + Sync {
+    /// Performs a GET request on the given URL.
+    fn get(&self, url: &str) -> Result<Response, LodestoneError>;
+
+    /// Performs a GET request for binary content, such as an image.
+    ///
+    /// Defaults to `Transport::get`, which only works for text bodies.
+    fn get_bytes(&self, url: &str) -> Result<Response<Vec<u8>>, LodestoneError> {
+        Ok(self.get(url)?.into_bytes())
+    }
+}
+
+/// Async counterpart of `Transport`, only available with the `async` feature.
+#[cfg(feature = "async")]
+pub trait AsyncTransport: Send + Sync {
+    /// Performs a GET request on the given URL.
+    fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response, LodestoneError>>;
+
+    /// Performs a GET request for binary content, such as an image.
+    ///
+    /// Defaults to `AsyncTransport::get`, which only works for text bodies.
+    fn get_bytes<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response<Vec<u8>>, LodestoneError>> {
+        Box::pin(async move { Ok(self.get(url).await?.into_bytes()) })
+    }
+}
+
+/// The default `Transport`, backed by a blocking reqwest client.
+///
+/// The client is only created on the first request, as blocking clients
+/// can't be created or dropped from within an async runtime, which would
+/// otherwise happen to clients built there only to make async requests.
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+    client: OnceLock<reqwest::blocking::Client>,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses an already configured client, e.g. one with a custom
+    /// user agent, proxy or timeout.
+    pub fn from_client(client: reqwest::blocking::Client) -> Self {
+        ReqwestTransport { client: OnceLock::from(client) }
+    }
+}
+
+impl ReqwestTransport {
+    fn send(&self, url: &str) -> Result<reqwest::blocking::Response, LodestoneError> {
+        Ok(self.client.get_or_init(reqwest::blocking::Client::new).get(url).send()?)
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn get(&self, url: &str) -> Result<Response, LodestoneError> {
+        let response = self.send(url)?;
+        let status = response.status().as_u16();
+        let retry_after = retry_after(response.headers());
+
+        Ok(Response {
+            status,
+            body: response.text()?,
+            retry_after,
+        })
+    }
+
+    fn get_bytes(&self, url: &str) -> Result<Response<Vec<u8>>, LodestoneError> {
+        let response = self.send(url)?;
+        let status = response.status().as_u16();
+        let retry_after = retry_after(response.headers());
+
+        Ok(Response {
+            status,
+            body: response.bytes()?.to_vec(),
+            retry_after,
+        })
+    }
+}
+
+/// The default `AsyncTransport`, backed by an async reqwest client.
+#[cfg(feature = "async")]
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestAsyncTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "async")]
+impl ReqwestAsyncTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses an already configured client, e.g. one with a custom
+    /// user agent, proxy or timeout.
+    pub fn from_client(client: reqwest::Client) -> Self {
+        ReqwestAsyncTransport { client }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for ReqwestAsyncTransport {
+    fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response, LodestoneError>> {
+        Box::pin(async move {
+            let response = self.client.get(url).send().await?;
+            let status = response.status().as_u16();
+            let retry_after = retry_after(response.headers());
+
+            Ok(Response {
+                status,
+                body: response.text().await?,
+                retry_after,
+            })
+        })
+    }
+
+    fn get_bytes<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response<Vec<u8>>, LodestoneError>> {
+        Box::pin(async move {
+            let response = self.client.get(url).send().await?;
+            let status = response.status().as_u16();
+            let retry_after = retry_after(response.headers());
+
+            Ok(Response {
+                status,
+                body: response.bytes().await?.to_vec(),
+                retry_after,
+            })
+        })
+    }
+}
+
+/// A client for the Lodestone through which every request is made.
+///
+/// `Profile::get` and `SearchBuilder::send` use a default client internally,
+/// so creating one is only needed to change how requests are performed.
+#[derive(Clone)]
+pub struct Lodestone {
+    domain: Domain,
+    limiter: Arc<RateLimiter>,
+    retry: RetryPolicy,
+    cache: Option<Arc<Cache>>,
+    concurrency: usize,
+    transport: Arc<dyn Transport>,
+    #[cfg(feature = "async")]
+    async_transport: Arc<dyn AsyncTransport>,
+}
+
+impl Default for Lodestone {
+    fn default() -> Self {
+        Lodestone {
+            domain: Domain::default(),
+            limiter: Arc::new(RateLimiter::new(RateLimit::default())),
+            retry: RetryPolicy::default(),
+            cache: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            transport: Arc::new(ReqwestTransport::new()),
+            #[cfg(feature = "async")]
+            async_transport: Arc::new(ReqwestAsyncTransport::new()),
+        }
+    }
+}
+
+impl Lodestone {
+    /// Creates a client using reqwest with its default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Which regional Lodestone to make requests to. Defaults to `Domain::NA`.
+    ///
+    /// Pages from every domain are parsed into the same models, so this
+    /// only changes which site is used and the language of free text.
+    pub fn with_domain(mut self, domain: Domain) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Limits how often requests are made. Defaults to `RateLimit::default()`.
+    ///
+    /// The limit is shared by the blocking and async API, and by every
+    /// clone of the client made after this call.
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.limiter = Arc::new(RateLimiter::new(limit));
+        self
+    }
+
+    /// How failed requests are retried. Defaults to `RetryPolicy::default()`.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Serves pages from a cache when they have been fetched recently.
+    /// Nothing is cached by default.
+    ///
+    /// The cache is shared by every clone of the client made after this call.
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// How many profiles `Lodestone::profiles` fetches at once. Defaults to 4.
+    ///
+    /// Requests still go through the rate limit, so this mostly helps by
+    /// waiting on several slow responses at the same time.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Replaces the transport used for blocking requests.
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Replaces the transport used for async requests.
+    #[cfg(feature = "async")]
+    pub fn with_async_transport<T: AsyncTransport + 'static>(mut self, transport: T) -> Self {
+        self.async_transport = Arc::new(transport);
+        self
+    }
+
+    /// Gets a profile for a user given their lodestone user id.
+    ///
+    /// The main page and the `class_job` page are fetched at the same time,
+    /// each still going through the rate limit.
+    ///
+    /// Fails with `LodestoneError::CharacterNotFound` if the character was
+    /// deleted or never existed, and with `LodestoneError::PrivateProfile`
+    /// if its owner has hidden it.
+    pub fn profile(&self, user_id: u32) -> Result<Profile, LodestoneError> {
+        let (main_html, classes_html) = std::thread::scope(|scope| {
+            let classes = scope.spawn(|| self.fetch(&profile_url(self.domain, user_id, Some("class_job"))));
+            let main = self.fetch(&profile_url(self.domain, user_id, None));
+
+            (main, classes.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+        });
+
+        Self::parse_profile(user_id, main_html, classes_html)
+    }
+
+    /// Async version of `Lodestone::profile`.
+    ///
+    /// Pages are kept as raw HTML until both have been fetched, since
+    /// documents are not `Send` and can't be held across awaits.
+    #[cfg(feature = "async")]
+    pub async fn profile_async(&self, user_id: u32) -> Result<Profile, LodestoneError> {
+        let main_url = profile_url(self.domain, user_id, None);
+        let classes_url = profile_url(self.domain, user_id, Some("class_job"));
+        let (main_html, classes_html) = tokio::join!(self.fetch_async(&main_url), self.fetch_async(&classes_url));
+
+        Self::parse_profile(user_id, main_html, classes_html)
+    }
+
+    /// Parses the pages of a profile once both have been fetched,
+    /// reporting the failure of the main page first.
+    fn parse_profile(
+        user_id: u32,
+        main_html: Result<String, LodestoneError>,
+        classes_html: Result<String, LodestoneError>,
+    ) -> Result<Profile, LodestoneError> {
+        let main_html = main_html.map_err(|e| e.for_character(user_id))?;
+        let classes_html = classes_html.map_err(|e| e.for_character(user_id))?;
+
+        Profile::from_html(user_id, &main_html, &classes_html)
+    }
+
+    /// Fetches the full profile of a search result.
+    pub fn resolve(&self, result: &SearchResult) -> Result<Profile, LodestoneError> {
+        self.profile(result.user_id)
+    }
+
+    /// Async version of `Lodestone::resolve`.
+    #[cfg(feature = "async")]
+    pub async fn resolve_async(&self, result: &SearchResult) -> Result<Profile, LodestoneError> {
+        self.profile_async(result.user_id).await
+    }
+
+    /// Gets the profiles of many characters, fetching several at once.
+    ///
+    /// Every id gets its own result, in the order they were given, so
+    /// a single failure doesn't prevent getting the other profiles.
+    pub fn profiles<I: IntoIterator<Item = u32>>(&self, user_ids: I) -> Vec<(u32, Result<Profile, LodestoneError>)> {
+        let user_ids = user_ids.into_iter().collect::<Vec<_>>();
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(user_ids.iter().map(|_| None).collect::<Vec<_>>());
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.concurrency.min(user_ids.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let user_id = match user_ids.get(index) {
+                        Some(&user_id) => user_id,
+                        None => break,
+                    };
+
+                    let profile = self.profile(user_id);
+                    results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(profile);
+                });
+            }
+        });
+
+        Self::collect_profiles(user_ids, results.into_inner().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Async version of `Lodestone::profiles`.
+    #[cfg(feature = "async")]
+    pub async fn profiles_async<I: IntoIterator<Item = u32>>(&self, user_ids: I) -> Vec<(u32, Result<Profile, LodestoneError>)> {
+        let user_ids = user_ids.into_iter().collect::<Vec<_>>();
+        let mut results = user_ids.iter().map(|_| None).collect::<Vec<_>>();
+        let mut tasks = tokio::task::JoinSet::new();
+
+        for (index, &user_id) in user_ids.iter().enumerate() {
+            if tasks.len() >= self.concurrency {
+                if let Some(task) = tasks.join_next().await {
+                    let (index, profile) = task.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+                    results[index] = Some(profile);
+                }
+            }
+
+            let client = self.clone();
+            tasks.spawn(async move { (index, client.profile_async(user_id).await) });
+        }
+
+        while let Some(task) = tasks.join_next().await {
+            let (index, profile) = task.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+            results[index] = Some(profile);
+        }
+
+        Self::collect_profiles(user_ids, results)
+    }
+
+    fn collect_profiles(
+        user_ids: Vec<u32>,
+        results: Vec<Option<Result<Profile, LodestoneError>>>,
+    ) -> Vec<(u32, Result<Profile, LodestoneError>)> {
+        user_ids
+            .into_iter()
+            .zip(results)
+            .map(|(user_id, profile)| (user_id, profile.expect("every profile is fetched")))
+            .collect()
+    }
+
+    /// Executes a search, returning a list of characters that match the given criteria.
+    pub fn search(&self, search: SearchBuilder) -> Result<Vec<SearchResult>, LodestoneError> {
+        Ok(self.search_page(search)?.results)
+    }
+
+    /// Executes a search, returning a single page of results along with the pagination info.
+    pub fn search_page(&self, search: SearchBuilder) -> Result<SearchPage, LodestoneError> {
+        let pages = search
+            .split_region()
+            .iter()
+            .map(|search| SearchPage::parse(&self.fetch(search.build_url(self.domain)?.as_str())?))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SearchPage::merge(search.page.unwrap_or(1), pages))
+    }
+
+    /// Returns an iterator which lazily fetches every page of a search.
+    pub fn search_iter(&self, search: SearchBuilder) -> SearchIter<'_> {
+        SearchIter::new(self, search)
+    }
+
+    /// Async version of `Lodestone::search`.
+    #[cfg(feature = "async")]
+    pub async fn search_async(&self, search: SearchBuilder) -> Result<Vec<SearchResult>, LodestoneError> {
+        Ok(self.search_page_async(search).await?.results)
+    }
+
+    /// Async version of `Lodestone::search_page`.
+    #[cfg(feature = "async")]
+    pub async fn search_page_async(&self, search: SearchBuilder) -> Result<SearchPage, LodestoneError> {
+        let mut pages = Vec::new();
+
+        for search in search.split_region() {
+            pages.push(SearchPage::parse(&self.fetch_async(search.build_url(self.domain)?.as_str()).await?)?);
+        }
+
+        Ok(SearchPage::merge(search.page.unwrap_or(1), pages))
+    }
+
+    /// Gets the current status of every server.
+    pub fn world_status(&self) -> Result<WorldStatus, LodestoneError> {
+        WorldStatus::from_html(&self.fetch(&format!("{}worldstatus/", self.domain.base_url()))?)
+    }
+
+    /// Async version of `Lodestone::world_status`.
+    #[cfg(feature = "async")]
+    pub async fn world_status_async(&self) -> Result<WorldStatus, LodestoneError> {
+        WorldStatus::from_html(&self.fetch_async(&format!("{}worldstatus/", self.domain.base_url())).await?)
+    }
+
+    /// Gets the notices of maintenance which is either ongoing or yet to come,
+    /// by reading the recent posts in the maintenance category of the news.
+    pub fn upcoming_maintenance(&self) -> Result<Vec<MaintenanceNotice>, LodestoneError> {
+        let now = SystemTime::now();
+        let mut upcoming = Vec::new();
+
+        for mut notice in self.recent_maintenance_notices(&self.fetch(&self.maintenance_feed_url())?, now)? {
+            notice.read_post(&self.fetch(&notice.url)?)?;
+            if notice.is_ongoing(now) || notice.is_upcoming(now) {
+                upcoming.push(notice);
+            }
+        }
+
+        Ok(upcoming)
+    }
+
+    /// Async version of `Lodestone::upcoming_maintenance`.
+    #[cfg(feature = "async")]
+    pub async fn upcoming_maintenance_async(&self) -> Result<Vec<MaintenanceNotice>, LodestoneError> {
+        let now = SystemTime::now();
+        let mut upcoming = Vec::new();
+        let feed = self.fetch_async(&self.maintenance_feed_url()).await?;
+
+        for mut notice in self.recent_maintenance_notices(&feed, now)? {
+            notice.read_post(&self.fetch_async(&notice.url).await?)?;
+            if notice.is_ongoing(now) || notice.is_upcoming(now) {
+                upcoming.push(notice);
+            }
+        }
+
+        Ok(upcoming)
+    }
+
+    fn maintenance_feed_url(&self) -> String {
+        format!("{}news/category/2", self.domain.base_url())
+    }
+
+    fn recent_maintenance_notices(&self, feed: &str, now: SystemTime) -> Result<Vec<MaintenanceNotice>, LodestoneError> {
+        let since = now - MAINTENANCE_LOOKBACK;
+
+        Ok(MaintenanceNotice::from_feed(feed, &self.domain.base_url())?
+            .into_iter()
+            .filter(|notice| notice.published >= since)
+            .collect())
+    }
+
+    /// Downloads an image, such as `Profile::portrait_url`, going through
+    /// the same rate limit, retries and cache as pages do.
+    pub fn image(&self, url: &str) -> Result<Vec<u8>, LodestoneError> {
+        self.fetch_raw(url, true)
+    }
+
+    /// Async version of `Lodestone::image`.
+    #[cfg(feature = "async")]
+    pub async fn image_async(&self, url: &str) -> Result<Vec<u8>, LodestoneError> {
+        self.fetch_raw_async(url, true).await
+    }
+
+    /// Fetches the body of a page.
+    pub(crate) fn fetch(&self, url: &str) -> Result<String, LodestoneError> {
+        Self::into_text(url, self.fetch_raw(url, false)?)
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) async fn fetch_async(&self, url: &str) -> Result<String, LodestoneError> {
+        Self::into_text(url, self.fetch_raw_async(url, false).await?)
+    }
+
+    /// Fetches a body from the cache, or through the transport,
+    /// retrying as many times as the retry policy allows.
+    fn fetch_raw(&self, url: &str, binary: bool) -> Result<Vec<u8>, LodestoneError> {
+        if let Some(body) = self.cached(url) {
+            return Ok(body);
+        }
+
+        let mut attempt = 1;
+
+        loop {
+            std::thread::sleep(self.limiter.reserve());
+            let response = match binary {
+                true => self.transport.get_bytes(url),
+                false => self.transport.get(url).map(Response::into_bytes),
+            };
+
+            if let Some(maintenance) = response.as_ref().ok().and_then(Self::maintenance) {
+                return Err(LodestoneError::Maintenance(maintenance));
+            }
+
+            match self.retry.delay(attempt, &response) {
+                Some(delay) => std::thread::sleep(delay),
+                None => return self.store(url, Self::check_status(response?)),
+            }
+            attempt += 1;
+        }
+    }
+
+    #[cfg(feature = "async")]
+    async fn fetch_raw_async(&self, url: &str, binary: bool) -> Result<Vec<u8>, LodestoneError> {
+        if let Some(body) = self.cached(url) {
+            return Ok(body);
+        }
+
+        let mut attempt = 1;
+
+        loop {
+            tokio::time::sleep(self.limiter.reserve()).await;
+            let response = match binary {
+                true => self.async_transport.get_bytes(url).await,
+                false => self.async_transport.get(url).await.map(Response::into_bytes),
+            };
+
+            if let Some(maintenance) = response.as_ref().ok().and_then(Self::maintenance) {
+                return Err(LodestoneError::Maintenance(maintenance));
+            }
+
+            match self.retry.delay(attempt, &response) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return self.store(url, Self::check_status(response?)),
+            }
+            attempt += 1;
+        }
+    }
+
+    fn into_text(url: &str, body: Vec<u8>) -> Result<String, LodestoneError> {
+        String::from_utf8(body).map_err(|_| LodestoneError::parse(url, "page is not valid UTF-8"))
+    }
+
+    fn cached(&self, url: &str) -> Option<Vec<u8>> {
+        self.cache.as_ref()?.get(url)
+    }
+
+    /// Caches successfully fetched bodies.
+    fn store(&self, url: &str, body: Result<Vec<u8>, LodestoneError>) -> Result<Vec<u8>, LodestoneError> {
+        if let (Some(cache), Ok(body)) = (&self.cache, &body) {
+            cache.put(url, body);
+        }
+        body
+    }
+
+    /// Finds the maintenance page, which is reported right away
+    /// instead of being retried like other 503s.
+    fn maintenance(response: &Response<Vec<u8>>) -> Option<Maintenance> {
+        //  Only pages can be the maintenance page, images never are
+        std::str::from_utf8(&response.body)
+            .ok()
+            .and_then(Maintenance::detect)
+    }
+
+    /// Turns unsuccessful responses into errors, so that
+    /// error pages never make it to the parsers.
+    fn check_status(response: Response<Vec<u8>>) -> Result<Vec<u8>, LodestoneError> {
+        match response.status {
+            200..=299 => Ok(response.body),
+            404 => Err(LodestoneError::NotFound),
+            429 => Err(LodestoneError::RateLimited),
+            status => Err(LodestoneError::HttpStatus(status)),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use thiserror::Error;
+
+use std::num::ParseIntError;
+
+use crate::model::{
+    city_state::CityStateParseError,
+    clan::ClanParseError,
+    class::ClassTypeParseError,
+    datacenter::DatacenterParseError,
+    domain::DomainParseError,
+    gc::{GrandCompanyParseError, GrandCompanyRankParseError},
+    gender::GenderParseError,
+    guardian::GuardianParseError,
+    language::LanguageParseError,
+    maintenance::Maintenance,
+    race::RaceParseError,
+    region::RegionParseError,
+    server::ServerParseError,
+    world_status::CongestionParseError,
+};
+use crate::search::QueryError;
+
+/// Represents every way in which a request to the Lodestone can fail.
+#[derive(Debug, Error)]
+pub enum LodestoneError {
+    /// The request could not be made or its response could not be read.
+    #[error("Network error: {0}")]
+    Network(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// A local file, such as a recorded response, could not be read or written.
+    #[error("I/O error: {0}")]
+    Io(#[source] std::io::Error),
+    /// The Lodestone answered with an unexpected HTTP status.
+    #[error("Unexpected HTTP status {0}")]
+    HttpStatus(u16),
+    /// The requested page does not exist.
+    #[error("Page not found")]
+    NotFound,
+    /// No character has this id, either because it was deleted
+    /// or because it never existed. The Lodestone answers both
+    /// the same way, so they can't be told apart.
+    #[error("Character {0} does not exist")]
+    CharacterNotFound(u32),
+    /// The character exists, but has made their profile private.
+    #[error("The profile of character {0} is private")]
+    PrivateProfile(u32),
+    /// The Lodestone is down for maintenance.
+    #[error("The Lodestone is under maintenance")]
+    Maintenance(Maintenance),
+    /// Too many requests were made, and the Lodestone refused to answer.
+    #[error("Rate limited by the Lodestone")]
+    RateLimited,
+    /// The search criteria were invalid, so no request was made.
+    #[error("Invalid search: {0}")]
+    InvalidQuery(#[from] QueryError),
+    /// A page was fetched, but its content could not be understood.
+    #[error("Could not parse {context}: {message}")]
+    Parse {
+        /// The selector or field that was being parsed.
+        context: String,
+        /// What went wrong.
+        message: String,
+    },
+}
+
+impl LodestoneError {
+    /// Creates a `LodestoneError::Parse` for the given context.
+    pub(crate) fn parse<C: Into<String>, M: Into<String>>(context: C, message: M) -> Self {
+        LodestoneError::Parse {
+            context: context.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Gives a more precise error for failed requests to a character's pages.
+    pub(crate) fn for_character(self, user_id: u32) -> Self {
+        match self {
+            LodestoneError::NotFound => LodestoneError::CharacterNotFound(user_id),
+            e => e,
+        }
+    }
+
+    /// Whether the same request might succeed if it is tried again later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            LodestoneError::Network(_) | LodestoneError::Maintenance(_) | LodestoneError::RateLimited => true,
+            LodestoneError::HttpStatus(status) => *status >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for LodestoneError {
+    fn from(e: reqwest::Error) -> Self {
+        LodestoneError::Network(Box::new(e))
+    }
+}
+
+impl From<url::ParseError> for LodestoneError {
+    fn from(e: url::ParseError) -> Self {
+        LodestoneError::parse("url", e.to_string())
+    }
+}
+
+impl From<ParseIntError> for LodestoneError {
+    fn from(e: ParseIntError) -> Self {
+        LodestoneError::parse("number", e.to_string())
+    }
+}
+
+/// Turns the errors from the `FromStr` impls of models into
+/// parse errors, using the kind of model as context.
+macro_rules! from_parse_error {
+    ($($error:ty => $context:expr),* $(,)?) => {
+        $(
+            impl From<$error> for LodestoneError {
+                fn from(e: $error) -> Self {
+                    LodestoneError::parse($context, e.to_string())
+                }
+            }
+        )*
+    };
+}
+
+from_parse_error! {
+    CityStateParseError => "city-state",
+    ClanParseError => "clan",
+    ClassTypeParseError => "class",
+    CongestionParseError => "congestion",
+    DatacenterParseError => "datacenter",
+    DomainParseError => "domain",
+    GenderParseError => "gender",
+    GrandCompanyParseError => "grand company",
+    GrandCompanyRankParseError => "grand company rank",
+    GuardianParseError => "guardian",
+    LanguageParseError => "language",
+    RaceParseError => "race",
+    RegionParseError => "region",
+    ServerParseError => "server",
+}
@@ -1,20 +1,1015 @@
-#[allow(unused)]
-
+pub mod cache;
+pub mod client;
+pub mod error;
 pub mod model;
+pub mod rate_limit;
+pub mod replay;
+pub mod retry;
 pub mod search;
 
+pub use crate::cache::Cache;
+pub use crate::client::Lodestone;
+pub use crate::error::LodestoneError;
+pub use crate::rate_limit::RateLimit;
+pub use crate::retry::RetryPolicy;
+
 // Lazy static client to avoid creating new ones every time
 lazy_static::lazy_static! {
-    static ref CLIENT: reqwest::blocking::Client = reqwest::blocking::Client::new();
+    static ref CLIENT: Lodestone = Lodestone::new();
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::client::{Response, Transport};
+    use crate::{Lodestone, LodestoneError};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    type Handler<B> = dyn Fn(&str) -> Result<Response<B>, LodestoneError> + Send + Sync;
+
+    /// A transport answering every request with a closure,
+    /// and counting how many requests reached it. Clones share the count.
+    struct FnTransport<B = String> {
+        handler: Arc<Handler<B>>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl<B> FnTransport<B> {
+        fn new<F>(handler: F) -> Self
+        where
+            F: Fn(&str) -> Result<Response<B>, LodestoneError> + Send + Sync + 'static,
+        {
+            FnTransport { handler: Arc::new(handler), calls: Arc::new(AtomicUsize::new(0)) }
+        }
+
+        /// The number of requests made so far, which keeps counting
+        /// once the transport is given to a client.
+        fn calls(&self) -> Arc<AtomicUsize> {
+            self.calls.clone()
+        }
+
+        fn handle(&self, url: &str) -> Result<Response<B>, LodestoneError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            (self.handler)(url)
+        }
+    }
+
+    impl<B> Clone for FnTransport<B> {
+        fn clone(&self) -> Self {
+            FnTransport { handler: self.handler.clone(), calls: self.calls.clone() }
+        }
+    }
+
+    impl Transport for FnTransport {
+        fn get(&self, url: &str) -> Result<Response, LodestoneError> {
+            self.handle(url)
+        }
+    }
+
+    /// Serves binary content only, such as images.
+    impl Transport for FnTransport<Vec<u8>> {
+        fn get(&self, url: &str) -> Result<Response, LodestoneError> {
+            panic!("{} should be fetched as bytes", url);
+        }
+
+        fn get_bytes(&self, url: &str) -> Result<Response<Vec<u8>>, LodestoneError> {
+            self.handle(url)
+        }
+    }
+
+    /// A client serving the pages checked into `tests/fixtures`.
+    ///
+    /// These are hand-written after the Lodestone's markup, in the format
+    /// `RecordingTransport` saves pages in, and are not real recordings.
+    fn fixtures() -> Lodestone {
+        use crate::replay::ReplayTransport;
+        use crate::{RateLimit, RetryPolicy};
+
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+        let client = Lodestone::new()
+            .with_rate_limit(RateLimit::unlimited())
+            .with_retry(RetryPolicy::none())
+            .with_transport(ReplayTransport::new(dir));
+
+        #[cfg(feature = "async")]
+        let client = client.with_async_transport(ReplayTransport::new(dir));
+
+        client
+    }
+
     #[test]
     fn can_grab_profile() {
+        assert!(fixtures().profile(11908971).is_ok());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn can_grab_profile_async() {
+        assert_eq!(fixtures().profile_async(11908971).await.unwrap(), fixtures().profile(11908971).unwrap());
+    }
+
+    #[test]
+    fn can_grab_many_profiles() {
+        use crate::RateLimit;
+        use std::time::Duration;
+
+        let profiles = fixtures().profiles([11908971, 1, 11908971]);
+
+        assert_eq!(profiles.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![11908971, 1, 11908971]);
+        assert_eq!(profiles[0].1.as_ref().unwrap().name, "Strawberry Custard");
+        assert!(matches!(profiles[1].1, Err(LodestoneError::Io(_))));
+        assert!(profiles[2].1.is_ok());
+
+        //  Keeps track of how many requests are made at the same time
+        let client = |concurrency, most_in_flight: &Arc<AtomicUsize>| {
+            let in_flight = AtomicUsize::new(0);
+            let most_in_flight = most_in_flight.clone();
+
+            Lodestone::new()
+                .with_rate_limit(RateLimit::unlimited())
+                .with_concurrency(concurrency)
+                .with_transport(FnTransport::new(move |_| {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    most_in_flight.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Ok(Response::new(404, ""))
+                }))
+        };
+
+        //  The two pages of a single profile are fetched at the same time
+        let most_in_flight = Arc::new(AtomicUsize::new(0));
+        assert!(matches!(client(1, &most_in_flight).profile(1), Err(LodestoneError::CharacterNotFound(1))));
+        assert_eq!(most_in_flight.load(Ordering::SeqCst), 2);
+
+        let most_in_flight = Arc::new(AtomicUsize::new(0));
+        let profiles = client(3, &most_in_flight).profiles(1..=9);
+
+        assert_eq!(profiles.len(), 9);
+        assert!(profiles.iter().all(|(id, profile)| matches!(profile, Err(LodestoneError::CharacterNotFound(i)) if i == id)));
+        assert!(most_in_flight.load(Ordering::SeqCst) > 2);
+        assert!(most_in_flight.load(Ordering::SeqCst) <= 6);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn can_grab_many_profiles_async() {
+        let profiles = fixtures().profiles_async([1, 11908971]).await;
+
+        assert_eq!(profiles[0].0, 1);
+        assert!(profiles[0].1.is_err());
+        assert_eq!(profiles[1].1.as_ref().unwrap().name, "Strawberry Custard");
+    }
+
+    #[test]
+    fn can_parse_profile_from_html() {
+        use crate::model::{
+            attribute::Resource,
+            city_state::CityState,
+            clan::Clan,
+            class::ClassType,
+            datacenter::Datacenter,
+            gender::Gender,
+            guardian::Guardian,
+            profile::Profile,
+            race::Race,
+            server::Server,
+        };
+
+        let main_html = r#"
+            <div class="frame__chara__name">Strawberry Custard</div>
+            <div class="frame__chara__world">Leviathan [Primal]</div>
+            <p class="character-block__name">Lalafell<br>Plainsfolk / ♀</p>
+            <p class="character-block__birth">3rd Sun of the 1st Umbral Moon</p>
+            <p class="character-block__name">Halone, the Fury</p>
+            <p class="character-block__name">Limsa Lominsa</p>
+            <div class="character__param"><ul>
+                <li><p class="character__param__text__hp--en-us">HP</p><span>15141</span></li>
+                <li><p class="character__param__text__mp--en-us">MP</p><span>10000</span></li>
+            </ul></div>
+            <table class="character__profile__data">
+                <tr><th><span>Strength</span></th><td>130</td></tr>
+            </table>
+        "#;
+        let classes_html = r#"
+            <div class="character__content"><ul>
+                <li>
+                    <div class="character__job__level">70</div>
+                    <div class="character__job__name">Black Mage</div>
+                    <div class="character__job__exp">0 / 2,923,000</div>
+                </li>
+                <li>
+                    <div class="character__job__level">-</div>
+                    <div class="character__job__name">Summoner</div>
+                    <div class="character__job__exp">-- / --</div>
+                </li>
+            </ul></div>
+        "#;
+
+        let profile = Profile::from_html(11908971, main_html, classes_html).unwrap();
+
+        assert_eq!(profile.user_id, 11908971);
+        assert_eq!(profile.name, "Strawberry Custard");
+        assert_eq!(profile.server, Server::Leviathan);
+        assert_eq!(profile.datacenter, Datacenter::Primal);
+        assert_eq!(profile.race, Race::Lalafell);
+        assert_eq!(profile.clan, Clan::Plainsfolk);
+        assert_eq!(profile.gender, Gender::Female);
+        assert_eq!(profile.guardian, Guardian::Halone);
+        assert_eq!(profile.city_state, CityState::LimsaLominsa);
+        assert_eq!(profile.hp, 15141);
+        assert_eq!(profile.resource, Resource::Mp(10000));
+        assert_eq!(profile.attributes.get("Strength").unwrap().level, 130);
+        assert_eq!(profile.level(ClassType::BlackMage), Some(70));
+        assert_eq!(profile.level(ClassType::Thaumaturge), Some(70));
+        assert_eq!(profile.level(ClassType::Summoner), None);
+    }
+
+    #[test]
+    fn can_parse_localized_profile() {
+        use crate::model::{
+            attribute::Resource,
+            city_state::CityState,
+            clan::Clan,
+            class::ClassType,
+            gc::{GrandCompany, GrandCompanyRank},
+            gear::GearSlot,
+            guardian::Guardian,
+            profile::Profile,
+            race::Race,
+        };
+        use std::str::FromStr;
+
+        let main_html = r#"
+            <div class="frame__chara__name">Erdbeer Pudding</div>
+            <div class="frame__chara__world">Odin [Light]</div>
+            <p class="character-block__name">Miqo'te<br>Goldtatze / ♂</p>
+            <p class="character-block__birth">3. Sonne im 1. Umbralmond</p>
+            <p class="character-block__name">Nald'thal - Die Händler</p>
+            <p class="character-block__name">Ul'dah</p>
+            <p class="character-block__name">Bruderschaft der Morgenviper / Schlangen-Gefreiter 2. Klasse</p>
+            <div class="character__param"><ul>
+                <li><p class="character__param__text__hp--de-de">LP</p><span>2000</span></li>
+                <li><p class="character__param__text__gp--de-de">SP</p><span>800</span></li>
+            </ul></div>
+            <table class="character__profile__data"></table>
+            <div class="icon-c--0">
+                <h2 class="db-tooltip__item__name">Eschenstab</h2>
+                <p class="db-tooltip__item__category">Zweihändige Waffe des Druiden</p>
+                <div class="db-tooltip__item__level">Gegenstandsstufe 90</div>
+            </div>
+            <div class="icon-c--13">
+                <h2 class="db-tooltip__item__name">Seelenkristall des Weißmagiers</h2>
+                <div class="db-tooltip__item__level">Gegenstandsstufe 30</div>
+            </div>
+        "#;
+        let classes_html = r#"
+            <div class="character__content"><ul>
+                <li>
+                    <div class="character__job__level">90</div>
+                    <div class="character__job__name">Weißmagierin</div>
+                    <div class="character__job__exp">-- / --</div>
+                </li>
+                <li>
+                    <div class="character__job__level">80</div>
+                    <div class="character__job__name">Blaumagierin (limitierter Job)</div>
+                    <div class="character__job__exp">-- / --</div>
+                </li>
+            </ul></div>
+        "#;
+
+        let profile = Profile::from_html(1, main_html, classes_html).unwrap();
+
+        assert_eq!(profile.race, Race::Miqote);
+        assert_eq!(profile.clan, Clan::SeekerOfTheSun);
+        assert_eq!(profile.guardian, Guardian::Naldthal);
+        assert_eq!(profile.city_state, CityState::Uldah);
+        assert_eq!(profile.grand_company, GrandCompany::TwinAdder);
+        assert_eq!(profile.grand_company_rank, Some(GrandCompanyRank::PrivateSecondClass));
+        assert_eq!(profile.hp, 2000);
+        assert_eq!(profile.resource, Resource::Gp(800));
+        assert_eq!(profile.active_class, Some(ClassType::WhiteMage));
+        assert_eq!(profile.level(ClassType::WhiteMage), Some(90));
+        assert_eq!(profile.level(ClassType::BlueMage), Some(80));
+
+        //  Without a soul crystal, the class comes from the weapon
+        let main_html = main_html
+            .replace("Miqo'te<br>Goldtatze", "ミコッテ<br>サンシーカー")
+            .replace("Nald'thal - Die Händler", "ナルザル")
+            .replace("Ul'dah", "ウルダハ")
+            .replace("Bruderschaft der Morgenviper / Schlangen-Gefreiter 2. Klasse", "双蛇党 / 一等蛇兵")
+            .replace("Zweihändige Waffe des Druiden", "両手幻術士用武器")
+            .replace("icon-c--13", "icon-c--none");
+        let classes_html = classes_html
+            .replace("Weißmagierin", "白魔道士")
+            .replace("Blaumagierin (limitierter Job)", "青魔道士（リミテッドジョブ）");
+
+        let profile = Profile::from_html(1, &main_html, &classes_html).unwrap();
+
+        assert_eq!(profile.race, Race::Miqote);
+        assert_eq!(profile.clan, Clan::SeekerOfTheSun);
+        assert_eq!(profile.guardian, Guardian::Naldthal);
+        assert_eq!(profile.city_state, CityState::Uldah);
+        assert_eq!(profile.grand_company, GrandCompany::TwinAdder);
+        assert_eq!(profile.grand_company_rank, Some(GrandCompanyRank::PrivateSecondClass));
+        assert_eq!(profile.active_class, Some(ClassType::Conjurer));
+        assert_eq!(profile.level(ClassType::WhiteMage), Some(90));
+        assert_eq!(profile.level(ClassType::BlueMage), Some(80));
+
+        //  Clans are named after the language of the Lodestone too
+        let profile = Profile::from_html(1, &main_html.replace("ミコッテ<br>サンシーカー", "Roegadyn<br>Lohengarde"), &classes_html).unwrap();
+        assert_eq!(profile.race, Race::Roegadyn);
+        assert_eq!(profile.clan, Clan::Hellsguard);
+
+        assert_eq!(Clan::from_str("Clan du Feu").unwrap(), Clan::Hellsguard);
+        assert_eq!(Clan::from_str("Die Verlorenen").unwrap(), Clan::TheLost);
+        assert_eq!(Clan::from_str("Hélions").unwrap(), Clan::Helions);
+        assert_eq!(Clan::from_str("ローエンガルデ").unwrap(), Clan::Hellsguard);
+        assert!(Clan::from_str("Lohgesicht").is_err());
+
+        //  Class names are found within item names and categories of any language
+        assert_eq!(ClassType::find_in("Âme de chevalier noir"), Some(ClassType::DarkKnight));
+        assert_eq!(ClassType::find_in("Arme à deux mains d'occultiste"), Some(ClassType::Thaumaturge));
+        assert_eq!(ClassType::find_in("Soul of the Black Mage"), Some(ClassType::BlackMage));
+        assert_eq!(ClassType::find_in("Gladiator's Arm"), Some(ClassType::Gladiator));
+        assert_eq!(ClassType::find_in("Ring"), None);
+
+        //  Ranks are read in every language, and unknown companies aren't an error
+        assert_eq!(GrandCompanyRank::from_name(GrandCompany::ImmortalFlames, "Sous-lieutenant des Immortels").unwrap(), GrandCompanyRank::SecondLieutenant);
+        assert_eq!(GrandCompanyRank::from_name(GrandCompany::Maelstrom, "Sturm-Oberleutnant").unwrap(), GrandCompanyRank::FirstLieutenant);
+        assert_eq!(GrandCompanyRank::from_name(GrandCompany::Maelstrom, "曹長").unwrap(), GrandCompanyRank::ChiefSergeant);
+        assert!(GrandCompanyRank::from_name(GrandCompany::Maelstrom, "大闘尉").is_err());
+        assert!(GrandCompanyRank::from_name(GrandCompany::ImmortalFlames, "Second Storm Lieutenant").is_err());
+
+        //  Neither is gear which can't be read, which is left out
+        let main_html = main_html
+            .replace("双蛇党 / 一等蛇兵", "Unbekannte Gesellschaft / Rekrut")
+            .replace("Gegenstandsstufe 90", "Gegenstandsstufe ???");
+        let profile = Profile::from_html(1, &main_html, &classes_html).unwrap();
+
+        assert_eq!(profile.grand_company, GrandCompany::Unaffiliated);
+        assert_eq!(profile.grand_company_rank, None);
+        assert_eq!(profile.gear.get(GearSlot::MainHand), None);
+        assert_eq!(profile.active_class, None);
+    }
+
+    #[test]
+    fn can_parse_search_results() {
+        use crate::model::{datacenter::Datacenter, language::Language, server::Server};
+        use crate::search::SearchBuilder;
+
+        let body = r#"
+            <div class="entry">
+                <a href="/lodestone/character/11908971/" class="entry__link">
+                    <div class="entry__chara__face"><img src="https://img2.finalfantasyxiv.com/f/face.jpg"></div>
+                    <div class="entry__box entry__box--world">
+                        <p class="entry__name">Strawberry Custard</p>
+                        <p class="entry__world"><i class="xiv-lds xiv-lds-home-world"></i>Leviathan [Primal]</p>
+                        <ul class="entry__chara_info">
+                            <li><i class="list__ic__class"><img src="https://img.finalfantasyxiv.com/class.png"></i><span>70</span></li>
+                            <li class="js__tooltip" data-tooltip="Maelstrom / Storm Captain"><img src="https://img.finalfantasyxiv.com/gc.png"></li>
+                            <li class="entry__chara__lang">English</li>
+                        </ul>
+                    </div>
+                </a>
+                <a href="/lodestone/freecompany/9229283011365743624/" class="entry__freecompany__link">
+                    <i class="list__ic__crest"></i><span>Custard Club</span>
+                </a>
+            </div>
+            <div class="entry">
+                <a href="/lodestone/character/123/" class="entry__link">
+                    <div class="entry__chara__face"><img src="https://img2.finalfantasyxiv.com/f/other.jpg"></div>
+                    <p class="entry__name">Strawberry Shortcake</p>
+                    <p class="entry__world">Tonberry [Elemental]</p>
+                    <ul class="entry__chara_info"></ul>
+                </a>
+            </div>
+            <div class="entry">
+                <a href="/lodestone/character/456/" class="entry__link">
+                    <div class="entry__chara__face"><img src="https://img2.finalfantasyxiv.com/f/new.jpg"></div>
+                    <p class="entry__name">Strawberry Newcomer</p>
+                    <p class="entry__world">Atlantis [Primal]</p>
+                </a>
+            </div>
+        "#;
+
+        let client = Lodestone::new().with_transport(FnTransport::new(move |_| Ok(Response::new(200, body))));
+        let results = client.search(SearchBuilder::new().character("Strawberry")).unwrap();
+
+        assert_eq!(results.len(), 2);
+
+        let custard = &results[0];
+        assert_eq!(custard.user_id, 11908971);
+        assert_eq!(custard.name, "Strawberry Custard");
+        assert_eq!(custard.server, Server::Leviathan);
+        assert_eq!(custard.datacenter, Datacenter::Primal);
+        assert_eq!(custard.face_url, "https://img2.finalfantasyxiv.com/f/face.jpg");
+        assert_eq!(custard.gc_rank_icon.as_deref(), Some("https://img.finalfantasyxiv.com/gc.png"));
+        assert_eq!(custard.free_company.as_deref(), Some("Custard Club"));
+        assert_eq!(custard.language, Some(Language::English));
+
+        let shortcake = &results[1];
+        assert_eq!(shortcake.user_id, 123);
+        assert_eq!(shortcake.server, Server::Tonberry);
+        assert_eq!(shortcake.gc_rank_icon, None);
+        assert_eq!(shortcake.free_company, None);
+        assert_eq!(shortcake.language, None);
+
+        //  An entry which can't be parsed doesn't take the others down with it
+        let page = client.search_page(SearchBuilder::new().character("Strawberry")).unwrap();
+        assert_eq!(page.results.len(), 2);
+        assert!(matches!(&page.errors[..], [LodestoneError::Parse { .. }]));
+
+        let results = client.search_iter(SearchBuilder::new().character("Strawberry")).collect::<Vec<_>>();
+        assert_eq!(results.len(), 3);
+        assert!(results[..2].iter().all(Result::is_ok));
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn can_walk_search_pages() {
+        use crate::search::SearchBuilder;
+
+        //  Serves two pages with a single result each
+        let client = Lodestone::new().with_transport(FnTransport::new(|url| {
+            let page = if url.ends_with("page=2") { 2 } else { 1 };
+            let body = format!(r#"
+                <div class="parts__total">2 Total</div>
+                <div class="entry">
+                    <a href="/lodestone/character/{page}/" class="entry__link">
+                        <div class="entry__chara__face"><img src="face.jpg"></div>
+                        <p class="entry__name">Result {page}</p>
+                        <p class="entry__world">Leviathan [Primal]</p>
+                    </a>
+                </div>
+                <ul class="btn__pager"><li class="btn__pager__current">Page {page} of 2</li></ul>
+            "#);
+
+            Ok(Response::new(200, body))
+        }));
+
+        let page = client.search_page(SearchBuilder::new().character("Result")).unwrap();
+        assert_eq!(page.page, 1);
+        assert_eq!(page.total_pages, 2);
+        assert_eq!(page.total_results, 2);
+        assert!(page.has_next());
+
+        let page = client.search_page(SearchBuilder::new().character("Result").page(2)).unwrap();
+        assert_eq!(page.page, 2);
+        assert!(!page.has_next());
+        assert_eq!(page.results[0].user_id, 2);
+
+        let ids = client
+            .search_iter(SearchBuilder::new().character("Result"))
+            .map(|result| result.unwrap().user_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn search_filters_are_in_url() {
+        use crate::model::{clan::Clan, class::ClassType, domain::Domain, gc::GrandCompany, language::Language, race::Race, server::Server};
+        use crate::search::{SearchBuilder, SortOrder};
+
+        let url = SearchBuilder::new()
+            .server(Server::Leviathan)
+            .class(ClassType::WhiteMage)
+            .race(Race::Viera)
+            .order(SortOrder::LevelDescending)
+            .build_url(Domain::NA)
+            .unwrap();
+
+        assert_eq!(url.as_str(), "https://na.finalfantasyxiv.com/lodestone/character/?worldname=Leviathan&classjob=24&race_tribe=race_8&order=5");
+
+        let url = SearchBuilder::new()
+            .race(Race::Viera)
+            .clan(Clan::Rava)
+            .build_url(Domain::NA)
+            .unwrap();
+
+        assert_eq!(url.as_str(), "https://na.finalfantasyxiv.com/lodestone/character/?race_tribe=tribe_15");
+
+        //  Repeated filters always come in the same order, as URLs are used as cache keys
+        let url = SearchBuilder::new()
+            .lang(Language::French)
+            .lang(Language::English)
+            .grand_company(GrandCompany::ImmortalFlames)
+            .grand_company(GrandCompany::Maelstrom)
+            .build_url(Domain::NA)
+            .unwrap();
+
+        assert_eq!(url.as_str(), "https://na.finalfantasyxiv.com/lodestone/character/?blog_lang=en&blog_lang=fr&gcid=1&gcid=3");
+    }
+
+    #[test]
+    fn search_names_are_encoded_and_validated() {
+        use crate::model::domain::Domain;
+        use crate::search::{QueryError, SearchBuilder};
+
+        let url = SearchBuilder::new()
+            .character("J'rhoomale Kinh-a")
+            .build_url(Domain::JP)
+            .unwrap();
+
+        assert_eq!(url.as_str(), "https://jp.finalfantasyxiv.com/lodestone/character/?q=J%27rhoomale+Kinh-a");
+
+        let url = SearchBuilder::new().character("Élodie").build_url(Domain::FR).unwrap();
+        assert_eq!(url.as_str(), "https://fr.finalfantasyxiv.com/lodestone/character/?q=%C3%89lodie");
+
+        let invalid = |name: &str| SearchBuilder::new().character(name).validate().unwrap_err();
+
+        assert_eq!(invalid("  "), QueryError::EmptyName);
+        assert_eq!(invalid("A B C"), QueryError::TooManyNameParts("A B C".into()));
+        assert_eq!(invalid("Straw&berry"), QueryError::InvalidCharacter("Straw&berry".into(), '&'));
+        assert_eq!(invalid("Abcdefghijklmnop"), QueryError::NameTooLong("Abcdefghijklmnop".into()));
+        assert_eq!(invalid("Abcdefghijk Lmnopqrstu"), QueryError::NameTooLong("Abcdefghijk Lmnopqrstu".into()));
+        assert_eq!(SearchBuilder::new().page(0).validate(), Err(QueryError::InvalidPage));
+    }
+
+    #[test]
+    fn servers_map_to_datacenters() {
+        use crate::model::{datacenter::Datacenter, region::Region, server::Server};
+        use std::str::FromStr;
+
+        let datacenters = [
+            Datacenter::Aether, Datacenter::Chaos, Datacenter::Crystal, Datacenter::Elemental,
+            Datacenter::Gaia, Datacenter::Light, Datacenter::Mana, Datacenter::Primal,
+            Datacenter::Materia, Datacenter::Shadow, Datacenter::Dynamis, Datacenter::Meteor,
+        ];
+
+        for dc in datacenters {
+            assert_eq!(Datacenter::from_str(&dc.to_string()).unwrap(), dc);
+
+            for server in dc.servers() {
+                assert_eq!(server.datacenter(), dc);
+            }
+        }
+
+        for region in [Region::NA, Region::EU, Region::OCE, Region::JP] {
+            for dc in region.datacenters() {
+                assert_eq!(dc.region(), region);
+            }
+        }
+
+        assert_eq!(Server::Halicarnassus.datacenter(), Datacenter::Dynamis);
+        assert_eq!(Server::Ramuh.datacenter(), Datacenter::Meteor);
+        assert_eq!(datacenters.iter().map(|dc| dc.servers().len()).sum::<usize>(), 89);
+    }
+
+    #[test]
+    fn region_search_covers_every_datacenter() {
+        use crate::model::{datacenter::Datacenter, region::Region};
+        use crate::search::SearchBuilder;
+
+        //  Serves a single result living on the datacenter that was searched
+        let client = Lodestone::new()
+            .with_rate_limit(crate::RateLimit::unlimited())
+            .with_transport(FnTransport::new(|url| {
+                let (id, world) = match url.split("_dc_").nth(1) {
+                    Some(dc) if dc.starts_with("Chaos") => (1, "Cerberus [Chaos]"),
+                    Some(dc) if dc.starts_with("Light") => (2, "Odin [Light]"),
+                    Some(dc) if dc.starts_with("Shadow") => (3, "Pixie [Shadow]"),
+                    _ => panic!("Unexpected URL {}", url),
+                };
+                let body = format!(r#"
+                    <div class="parts__total">1 Total</div>
+                    <div class="entry">
+                        <a href="/lodestone/character/{id}/" class="entry__link">
+                            <div class="entry__chara__face"><img src="face.jpg"></div>
+                            <p class="entry__name">Result {id}</p>
+                            <p class="entry__world">{world}</p>
+                        </a>
+                    </div>
+                "#);
+
+                Ok(Response::new(200, body))
+            }));
+
+        let page = client.search_page(SearchBuilder::new().character("Result").region(Region::EU)).unwrap();
+        assert_eq!(page.total_results, 3);
+        assert_eq!(
+            page.results.iter().map(|r| r.datacenter).collect::<Vec<_>>(),
+            vec![Datacenter::Chaos, Datacenter::Light, Datacenter::Shadow]
+        );
+
+        let ids = client
+            .search_iter(SearchBuilder::new().character("Result").region(Region::EU))
+            .map(|result| result.unwrap().user_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn can_parse_world_status() {
+        use crate::model::server::Server;
+        use crate::model::world_status::{Congestion, ServerState, WorldStatus};
+
+        let html = r#"
+            <div class="world-dcgroup__item">
+                <h2 class="world-dcgroup__header">Primal</h2>
+                <ul>
+                    <li class="item-list"><div class="world-list__item">
+                        <div class="world-list__status_icon"><i class="world-ic__1 js__tooltip" data-tooltip=" Online"></i></div>
+                        <div class="world-list__world_name"><p>Leviathan</p></div>
+                        <div class="world-list__world_category"><p>Congested</p></div>
+                        <div class="world-list__create_character"><i class="world-ic__unavailable js__tooltip"></i></div>
+                    </div></li>
+                    <li class="item-list"><div class="world-list__item">
+                        <div class="world-list__status_icon"><i class="world-ic__3 js__tooltip" data-tooltip=" Maintenance"></i></div>
+                        <div class="world-list__world_name"><p>Ultros</p></div>
+                        <div class="world-list__world_category"><p>Preferred</p></div>
+                        <div class="world-list__create_character"><i class="world-ic__available js__tooltip"></i></div>
+                    </div></li>
+                    <li class="item-list"><div class="world-list__item">
+                        <div class="world-list__status_icon"><i class="world-ic__1 js__tooltip" data-tooltip=" Online"></i></div>
+                        <div class="world-list__world_name"><p>Atlantis</p></div>
+                        <div class="world-list__world_category"><p>New</p></div>
+                        <div class="world-list__create_character"><i class="world-ic__available js__tooltip"></i></div>
+                    </div></li>
+                </ul>
+            </div>
+        "#;
+
+        let status = WorldStatus::from_html(html).unwrap();
+
+        //  Worlds which aren't known yet are left out, without hiding the others
+        assert_eq!(status.iter().count(), 2);
+        assert_eq!(status.errors().len(), 1);
+
+        let leviathan = status.status(Server::Leviathan).unwrap();
+        assert_eq!(leviathan.state, ServerState::Online);
+        assert_eq!(leviathan.congestion, Congestion::Congested);
+        assert!(!leviathan.character_creation);
+
+        let ultros = status.status(Server::Ultros).unwrap();
+        assert_eq!(ultros.state, ServerState::Maintenance);
+        assert_eq!(ultros.congestion, Congestion::Preferred);
+        assert!(ultros.character_creation);
+
+        assert_eq!(status.status(Server::Cactuar), None);
+    }
+
+    #[test]
+    fn requests_go_through_transport() {
+        use crate::model::datacenter::Datacenter;
+        use crate::model::domain::Domain;
+        use crate::model::server::Server;
+        use crate::search::{SearchBuilder, SearchResult};
+        use std::sync::Mutex;
+
+        //  Serves an empty page and remembers which URLs were requested
+        let urls = Arc::new(Mutex::new(Vec::new()));
+        let requested = urls.clone();
+        let client = Lodestone::new().with_transport(FnTransport::new(move |url| {
+            requested.lock().unwrap().push(url.to_string());
+            Ok(Response::new(200, "<html></html>"))
+        }));
+
+        let results = client
+            .search(SearchBuilder::new().character("Strawberry").datacenter(Datacenter::Primal))
+            .unwrap();
+        assert!(results.is_empty());
+        assert!(client.profile(11908971).is_err());
+
+        //  Results are resolved through the client they are given to
+        let result = SearchResult {
+            user_id: 11908971,
+            name: "Strawberry Custard".to_string(),
+            server: Server::Leviathan,
+            datacenter: Datacenter::Primal,
+            face_url: String::new(),
+            gc_rank_icon: None,
+            free_company: None,
+            language: None,
+        };
+        let client = client.with_domain(Domain::DE);
+        assert!(client.resolve(&result).is_err());
+
+        //  Both pages of a profile are fetched at once, so they may come in any order
+        let mut urls = urls.lock().unwrap().clone();
+        urls.sort();
+        assert_eq!(urls, vec![
+            "https://de.finalfantasyxiv.com/lodestone/character/11908971/",
+            "https://de.finalfantasyxiv.com/lodestone/character/11908971/class_job/",
+            "https://na.finalfantasyxiv.com/lodestone/character/11908971/",
+            "https://na.finalfantasyxiv.com/lodestone/character/11908971/class_job/",
+            "https://na.finalfantasyxiv.com/lodestone/character/?q=Strawberry&worldname=_dc_Primal",
+        ]);
+    }
+
+    #[test]
+    fn errors_are_typed() {
+        use crate::search::{QueryError, SearchBuilder};
+
+        //  Answers every request with the same status and an empty page
+        let client = |status| Lodestone::new()
+            .with_retry(crate::RetryPolicy::none())
+            .with_transport(FnTransport::new(move |_| Ok(Response::new(status, "<html></html>"))));
+
+        assert!(matches!(client(200).profile(1), Err(LodestoneError::Parse { .. })));
+        assert!(matches!(client(404).profile(1), Err(LodestoneError::CharacterNotFound(1))));
+        assert!(matches!(client(404).world_status(), Err(LodestoneError::NotFound)));
+        assert!(matches!(client(429).profile(1), Err(LodestoneError::RateLimited)));
+        assert!(matches!(client(500).world_status(), Err(LodestoneError::HttpStatus(500))));
+        assert!(matches!(client(503).world_status(), Err(LodestoneError::HttpStatus(503))));
+        assert!(matches!(
+            client(200).search(SearchBuilder::new().character("A B C")),
+            Err(LodestoneError::InvalidQuery(QueryError::TooManyNameParts(_)))
+        ));
+
+        assert!(LodestoneError::HttpStatus(503).is_transient());
+        assert!(!LodestoneError::NotFound.is_transient());
+    }
+
+    #[test]
+    fn rate_limit_delays_bursts() {
+        use crate::rate_limit::{RateLimit, RateLimiter};
+        use std::time::{Duration, Instant};
+
+        let limiter = RateLimiter::new(RateLimit::new(2.0, 3));
+        let start = Instant::now();
+
+        //  The burst goes through right away, then each request waits its turn
+        let waits = (0..5).map(|_| limiter.reserve_at(start)).collect::<Vec<_>>();
+        assert_eq!(waits, vec![
+            Duration::ZERO,
+            Duration::ZERO,
+            Duration::ZERO,
+            Duration::from_millis(500),
+            Duration::from_millis(1000),
+        ]);
+
+        //  Idle time refills the bucket, but never beyond the burst
+        assert_eq!(limiter.reserve_at(start + Duration::from_secs(60)), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(start + Duration::from_secs(60)), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(start + Duration::from_secs(60)), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(start + Duration::from_secs(60)), Duration::from_millis(500));
+
+        let unlimited = RateLimiter::new(RateLimit::unlimited());
+        assert!((0..100).all(|_| unlimited.reserve_at(start) == Duration::ZERO));
+    }
+
+    #[test]
+    fn transient_failures_are_retried() {
+        use crate::retry::RetryPolicy;
+        use crate::RateLimit;
+        use std::sync::atomic::AtomicU32;
+        use std::time::Duration;
+
+        let policy = RetryPolicy::new()
+            .max_attempts(4)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1))
+            .jitter(false);
+
+        let unavailable = Ok(Response::new(503, ""));
+        assert_eq!(policy.delay(1, &unavailable), Some(Duration::from_millis(100)));
+        assert_eq!(policy.delay(2, &unavailable), Some(Duration::from_millis(200)));
+        assert_eq!(policy.delay(3, &unavailable), Some(Duration::from_millis(400)));
+        assert_eq!(policy.delay(4, &unavailable), None);
+
+        let network: Result<Response, _> = Err(LodestoneError::Network("connection reset".into()));
+        assert_eq!(policy.delay(1, &network), Some(Duration::from_millis(100)));
+        assert_eq!(policy.clone().retry_network(false).delay(1, &network), None);
+        assert_eq!(policy.clone().retry_on(&[]).delay(1, &unavailable), None);
+        assert_eq!(policy.delay(1, &Ok(Response::new(404, ""))), None);
+        assert_eq!(policy.delay(1, &Ok(Response::new(200, ""))), None);
+
+        //  Retry-After is honoured, unless it asks to wait longer than allowed
+        let throttled = Response::new(429, "");
+        assert_eq!(policy.delay(1, &Ok(throttled.clone().with_retry_after(Duration::from_millis(700)))), Some(Duration::from_millis(700)));
+        assert_eq!(policy.delay(1, &Ok(throttled.with_retry_after(Duration::from_secs(60)))), None);
+
+        let jittered = RetryPolicy::new().base_delay(Duration::from_millis(100));
+        for _ in 0..20 {
+            let delay = jittered.delay(1, &unavailable).unwrap();
+            assert!(delay > Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+
+        //  Fails with a 502 a given number of times before succeeding
+        let client = |failures| {
+            let failures = AtomicU32::new(failures);
+
+            Lodestone::new()
+                .with_rate_limit(RateLimit::unlimited())
+                .with_retry(RetryPolicy::new().max_attempts(3).base_delay(Duration::from_millis(1)))
+                .with_transport(FnTransport::new(move |_| match failures.fetch_sub(1, Ordering::SeqCst) {
+                    0 => Ok(Response::new(200, "<html></html>")),
+                    _ => Ok(Response::new(502, "Bad Gateway")),
+                }))
+        };
+
+        assert!(client(2).world_status().unwrap().iter().next().is_none());
+        assert!(matches!(client(3).world_status(), Err(LodestoneError::HttpStatus(502))));
+    }
+
+    #[test]
+    fn pages_are_cached() {
+        use crate::cache::{Cache, PageKind};
+        use crate::RateLimit;
+        use std::time::Duration;
+
+        assert_eq!(PageKind::of("https://na.finalfantasyxiv.com/lodestone/character/1/"), PageKind::Profile);
+        assert_eq!(PageKind::of("https://na.finalfantasyxiv.com/lodestone/character/1/class_job/"), PageKind::ClassJob);
+        assert_eq!(PageKind::of("https://na.finalfantasyxiv.com/lodestone/character/?q=A"), PageKind::Search);
+        assert_eq!(PageKind::of("https://na.finalfantasyxiv.com/lodestone/worldstatus/"), PageKind::WorldStatus);
+        assert_eq!(PageKind::of("https://na.finalfantasyxiv.com/lodestone/news/category/2"), PageKind::News);
+
+        let dir = std::env::temp_dir().join(format!("lodestone-cache-{}", std::process::id()));
+        let transport = FnTransport::new(|_| Ok(Response::new(200, "<html></html>")));
+        let requests = transport.calls();
+        let client = |cache: Cache| Lodestone::new()
+            .with_rate_limit(RateLimit::unlimited())
+            .with_cache(cache)
+            .with_transport(transport.clone());
+
+        let cache = Cache::new(10)
+            .ttl(PageKind::WorldStatus, Duration::ZERO)
+            .persist_to(&dir);
+        let lodestone = client(cache);
+
+        //  Both character pages are fetched once, even though parsing them fails
+        assert!(lodestone.profile(1).is_err());
+        assert!(lodestone.profile(1).is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        //  The world status expires right away
+        lodestone.world_status().unwrap();
+        lodestone.world_status().unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+
+        //  A new cache over the same directory still has the character pages
+        let lodestone = client(Cache::new(10).persist_to(&dir));
+        assert!(lodestone.profile(1).is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+
+        //  Only the most recently used page is kept in memory
+        let lodestone = client(Cache::new(1));
+        lodestone.world_status().unwrap();
+        assert!(lodestone.profile(2).is_err());
+        lodestone.world_status().unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 8);
+
+        //  A page which wasn't completely written is fetched again
+        let page = dir.join("lodestone-cache").join(crate::cache::file_name("https://na.finalfantasyxiv.com/lodestone/character/1/"));
+        let contents = std::fs::read(&page).unwrap();
+        std::fs::write(&page, &contents[..contents.len() - 3]).unwrap();
+        assert!(client(Cache::new(10).persist_to(&dir)).profile(1).is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 9);
+
+        //  Both character pages and the world status are moved into place, leaving nothing else behind
+        let files = std::fs::read_dir(dir.join("lodestone-cache")).unwrap().flatten().collect::<Vec<_>>();
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|file| !file.file_name().to_string_lossy().ends_with(".tmp")));
+
+        //  Clearing leaves alone anything the cache didn't write
+        std::fs::write(dir.join("notes.txt"), "keep me").unwrap();
+        Cache::new(1).persist_to(&dir).clear();
+        assert!(!dir.join("lodestone-cache").exists());
+        assert!(dir.join("notes.txt").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn images_are_downloaded() {
+        use crate::cache::{Cache, PageKind};
+        use crate::RateLimit;
+
+        const JPEG: [u8; 4] = [0xFF, 0xD8, 0xFF, 0xE0];
+        let url = "https://img2.finalfantasyxiv.com/f/abc_640x873.jpg?1565000000";
+        assert_eq!(PageKind::of(url), PageKind::Image);
+
+        //  Serves the same image for every request
+        let transport = FnTransport::new(|_| Ok(Response { status: 200, body: JPEG.to_vec(), retry_after: None }));
+        let requests = transport.calls();
+        let lodestone = Lodestone::new()
+            .with_rate_limit(RateLimit::unlimited())
+            .with_cache(Cache::new(10))
+            .with_transport(transport);
+
+        assert_eq!(lodestone.image(url).unwrap(), JPEG);
+        assert_eq!(lodestone.image(url).unwrap(), JPEG);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn recorded_responses_are_replayed() {
+        use crate::replay::{RecordingTransport, ReplayTransport};
+
+        let not_found = FnTransport::new(|url| Ok(Response::new(404, format!("<p>{}\nnot found</p>", url))));
+
+        let dir = std::env::temp_dir().join(format!("lodestone-fixtures-{}", std::process::id()));
+        let url = "https://na.finalfantasyxiv.com/lodestone/character/?q=J%27rhoomale&page=2";
+
+        let recorded = RecordingTransport::new(&dir, not_found.clone()).get(url).unwrap();
+        let replayed = ReplayTransport::new(&dir).get(url).unwrap();
+        assert_eq!(recorded, replayed);
+
+        assert!(matches!(ReplayTransport::new(&dir).get("https://na.finalfantasyxiv.com/"), Err(LodestoneError::Io(_))));
+
+        //  Long URLs still fit in a file name, and don't collide with each other
+        let long = |page| format!("https://na.finalfantasyxiv.com/lodestone/character/?q=%C3%89lodie{}&page={}", "&blog_lang=ja&gcid=1".repeat(10), page);
+        assert!(crate::cache::file_name(&long(1)).len() <= 200);
+        assert_ne!(crate::cache::file_name(&long(1)), crate::cache::file_name(&long(2)));
+
+        let recorded = RecordingTransport::new(&dir, not_found).get(&long(1)).unwrap();
+        assert_eq!(ReplayTransport::new(&dir).get(&long(1)).unwrap(), recorded);
+        assert!(matches!(ReplayTransport::new(&dir).get(&long(2)), Err(LodestoneError::Io(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn can_detect_maintenance() {
+        use crate::model::maintenance::MaintenanceNotice;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let body = r#"
+            <div class="maintenance__body">
+                <p>The Lodestone is currently undergoing maintenance.</p>
+                <p>[Date &amp; Time]<br>
+                <span id="datetime-1"></span><script>document.getElementById('datetime-1').innerHTML = ldst_strftime(1700000000, 'YMDHM');</script> to
+                <span id="datetime-2"></span><script>document.getElementById('datetime-2').innerHTML = ldst_strftime(1700028800, 'YMDHM');</script></p>
+            </div>
+        "#;
+
+        for status in [200, 503] {
+            let transport = FnTransport::new(move |_| Ok(Response::new(status, body)));
+            let requests = transport.calls();
+            let client = Lodestone::new()
+                .with_rate_limit(crate::RateLimit::unlimited())
+                .with_transport(transport);
+
+            //  Maintenance is reported right away, even though other 503s are retried
+            match client.world_status() {
+                Err(LodestoneError::Maintenance(maintenance)) => {
+                    assert_eq!(maintenance.ends_at, Some(UNIX_EPOCH + Duration::from_secs(1700028800)));
+                }
+                other => panic!("expected maintenance, got {:?}", other),
+            }
+            assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+            assert!(matches!(client.profile(11908971), Err(LodestoneError::Maintenance(_))));
+        }
+
+        let feed = r#"
+            <ul>
+                <li class="news__list"><a href="/lodestone/news/detail/abc123" class="news__list--link">
+                    <p class="news__list--title"><span class="news__list--tag">[Maintenance]</span>All Worlds Maintenance (Nov. 14)</p>
+                    <time class="news__list--time"><span id="datetime-3"></span><script>document.getElementById('datetime-3').innerHTML = ldst_strftime(1699800000, 'YMD');</script></time>
+                </a></li>
+            </ul>
+        "#;
+
+        let mut notices = MaintenanceNotice::from_feed(feed, "https://na.finalfantasyxiv.com/lodestone/").unwrap();
+        assert_eq!(notices.len(), 1);
+
+        let notice = &mut notices[0];
+        assert_eq!(notice.title, "All Worlds Maintenance (Nov. 14)");
+        assert_eq!(notice.url, "https://na.finalfantasyxiv.com/lodestone/news/detail/abc123");
+        assert_eq!(notice.published, UNIX_EPOCH + Duration::from_secs(1699800000));
+
+        notice.read_post(r#"
+            <div class="news__detail__wrapper">
+                [Date &amp; Time]<br>
+                <span id="datetime-4"></span><script>document.getElementById('datetime-4').innerHTML = ldst_strftime(1699948800, 'YMDHM');</script> to
+                <span id="datetime-5"></span><script>document.getElementById('datetime-5').innerHTML = ldst_strftime(1699977600, 'YMDHM');</script>
+            </div>
+        "#).unwrap();
+
+        assert!(notice.is_upcoming(UNIX_EPOCH + Duration::from_secs(1699900000)));
+        assert!(notice.is_ongoing(UNIX_EPOCH + Duration::from_secs(1699950000)));
+        assert!(!notice.is_ongoing(UNIX_EPOCH + Duration::from_secs(1699980000)));
+    }
+
+    #[test]
+    fn missing_and_private_characters_are_detected() {
         use crate::model::profile::Profile;
 
-        assert!(Profile::get(11908971).is_ok());
+        let private_html = r#"
+            <div class="frame__chara__name">Strawberry Custard</div>
+            <div class="frame__chara__world">Leviathan [Primal]</div>
+            <div class="character__content selected">
+                <p class="parts__zero">This character's profile is private.</p>
+            </div>
+        "#;
+
+        assert!(matches!(
+            Profile::from_html(11908971, private_html, private_html),
+            Err(LodestoneError::PrivateProfile(11908971))
+        ));
+
+        //  Serves the private page for one character, and a 404 for everyone else
+        let client = Lodestone::new().with_transport(FnTransport::new(move |url| {
+            Ok(match url.contains("/11908971/") {
+                true => Response::new(200, private_html),
+                false => Response::new(404, "<h1 class=\"error__heading\">Not Found</h1>"),
+            })
+        }));
+
+        assert!(matches!(client.profile(11908971), Err(LodestoneError::PrivateProfile(11908971))));
+        assert!(matches!(client.profile(1), Err(LodestoneError::CharacterNotFound(1))));
     }
 
     #[test]
@@ -24,12 +1019,12 @@ mod tests {
         use crate::model::language::Language;
         use crate::search::SearchBuilder;
 
-        let profiles = SearchBuilder::new()
-            .character("Strawberry Custard")
-            .datacenter(Datacenter::Primal)
-            .lang(Language::English)
-            .grand_company(GrandCompany::Maelstrom)
-            .send()
+        let profiles = fixtures()
+            .search(SearchBuilder::new()
+                .character("Strawberry Custard")
+                .datacenter(Datacenter::Primal)
+                .lang(Language::English)
+                .grand_company(GrandCompany::Maelstrom))
             .unwrap();
 
         assert_eq!(profiles.len(), 1);
@@ -38,32 +1033,50 @@ mod tests {
     #[test]
     fn profile_is_correct() {
         use crate::model::{
+            attribute::Resource,
+            city_state::CityState,
             clan::Clan,
             class::ClassType,
             datacenter::Datacenter,
-            gc::GrandCompany,
+            domain::Domain,
+            gc::{GrandCompany, GrandCompanyRank},
+            gear::{GearSlot, Glamour},
             gender::Gender,
+            guardian::Guardian,
             language::Language,
             race::Race,
         };
         use crate::search::SearchBuilder;
 
-        let profiles = SearchBuilder::new()
-            .character("Strawberry Custard")
-            .datacenter(Datacenter::Primal)
-            .lang(Language::English)
-            .grand_company(GrandCompany::Maelstrom)
-            .send()
+        let lodestone = fixtures();
+        let profiles = lodestone
+            .search(SearchBuilder::new()
+                .character("Strawberry Custard")
+                .datacenter(Datacenter::Primal)
+                .lang(Language::English)
+                .grand_company(GrandCompany::Maelstrom))
             .unwrap();
 
         assert_eq!(profiles.len(), 1);
 
-        let strawberry = profiles.get(0).unwrap();
+        let strawberry = lodestone.profile(profiles.first().unwrap().user_id).unwrap();
 
         assert_eq!(strawberry.name, "Strawberry Custard");
         assert_eq!(strawberry.nameday, "3rd Sun of the 1st Umbral Moon");
-        assert_eq!(strawberry.guardian, "Halone, the Fury");
-        assert_eq!(strawberry.city_state, "Limsa Lominsa");
+        assert_eq!(strawberry.guardian, Guardian::Halone);
+        assert_eq!(strawberry.city_state, CityState::LimsaLominsa);
+        assert_eq!(strawberry.grand_company, GrandCompany::Maelstrom);
+
+        let free_company = strawberry.free_company.as_ref().unwrap();
+        assert_eq!(free_company.id, 9229283011365743624);
+        assert_eq!(free_company.name, "Custard Club");
+        assert_eq!(free_company.url(Domain::EU), "https://eu.finalfantasyxiv.com/lodestone/freecompany/9229283011365743624/");
+        assert_eq!(free_company.crest.len(), 3);
+        assert!(free_company.crest[0].contains("/c/B4_"));
+        assert_eq!(strawberry.grand_company_rank, Some(GrandCompanyRank::SecondLieutenant));
+        assert_eq!(GrandCompanyRank::SecondLieutenant.name(GrandCompany::TwinAdder).unwrap(), "Second Serpent Lieutenant");
+        assert_eq!(GrandCompanyRank::from_name(GrandCompany::ImmortalFlames, "Flame Private First Class").unwrap(), GrandCompanyRank::PrivateFirstClass);
+        assert!(GrandCompanyRank::from_name(GrandCompany::ImmortalFlames, "Storm Captain").is_err());
 
         assert_eq!(strawberry.race, Race::Lalafell);
         assert_eq!(strawberry.clan, Clan::Plainsfolk);
@@ -71,7 +1084,16 @@ mod tests {
         assert_eq!(strawberry.level(ClassType::BlackMage), Some(70));
 
         assert_eq!(strawberry.hp, 15141);
-        assert_eq!(strawberry.mp, 10000);
+        assert_eq!(strawberry.resource, Resource::Mp(10000));
+        assert_eq!(strawberry.active_class, Some(ClassType::BlackMage));
+        assert_eq!(strawberry.average_item_level, Some(361));
+        assert_eq!(strawberry.gear.average_item_level(), Some(361));
+        assert!(strawberry.portrait_url.as_deref().is_some_and(|url| url.ends_with("_640x873.jpg?1565000000")));
+        assert!(strawberry.avatar_url.as_deref().is_some_and(|url| url.ends_with("_96x96.jpg?1565000000")));
+
+        let bio = strawberry.bio.as_ref().unwrap();
+        assert_eq!(bio.as_text(), "Hi! I'm a <Black Mage> & part-time *crafter*.\nFind me in Limsa every night.\n\n# not a heading");
+        assert_eq!(bio.to_markdown(), "Hi! I'm a \\<Black Mage\\> & part-time \\*crafter\\*.\\\nFind me in Limsa every night.\n\n\\# not a heading");
 
         let attribs = &strawberry.attributes;
 
@@ -100,6 +1122,32 @@ mod tests {
 
         assert_eq!(attribs.get("Invalid Attribute"), None);
 
+        let gear = &strawberry.gear;
+
+        assert_eq!(gear.iter().count(), 12);
+        assert_eq!(gear.get(GearSlot::OffHand), None);
+
+        let weapon = gear.get(GearSlot::MainHand).unwrap();
+        assert_eq!(weapon.name, "Scaevan Magitek Rod");
+        assert_eq!(weapon.item_id.as_deref(), Some("6c0bbb8e2b1"));
+        assert_eq!(weapon.item_level, 370);
+        assert!(!weapon.hq);
+        assert_eq!(weapon.materia, vec!["Savage Aim Materia VI", "Savage Might Materia VI"]);
+        assert_eq!(weapon.glamour, Some(Glamour {
+            name: "Rod of the Black Griffin".into(),
+            item_id: Some("a1b2c3d4e5f".into()),
+        }));
+        assert_eq!(weapon.dye, None);
+
+        let hat = gear.get(GearSlot::Head).unwrap();
+        assert_eq!(hat.name, "Ghost Barque Hat of Casting");
+        assert!(hat.hq);
+        assert_eq!(hat.glamour, None);
+        assert_eq!(hat.dye.as_deref(), Some("Jet Black"));
+
+        assert_eq!(gear.get(GearSlot::Ring2).unwrap().name, "Ghost Barque Ring of Casting");
+        assert_eq!(gear.get(GearSlot::SoulCrystal).unwrap().name, "Soul of the Black Mage");
+
         let classes = &strawberry.all_class_info();
 
         assert_eq!(classes.get(ClassType::Paladin), None);
@@ -1,11 +1,20 @@
 pub mod attribute;
+pub mod bio;
+pub mod city_state;
 pub mod clan;
 pub mod class;
 pub mod datacenter;
+pub mod domain;
+pub mod free_company;
 pub mod gc;
+pub mod gear;
 pub mod gender;
+pub mod guardian;
 pub mod language;
+pub mod maintenance;
 pub mod profile;
 pub mod race;
+pub mod region;
 pub mod server;
 pub(crate) mod util;
+pub mod world_status;
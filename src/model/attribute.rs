@@ -7,6 +7,26 @@ pub struct Attribute {
     pub level: u16
 }
 
+/// The second bar under HP, whose stat depends on the active class.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Resource {
+    /// MP, used by Disciples of War and Magic.
+    Mp(u32),
+    /// GP, used by Disciples of the Land.
+    Gp(u32),
+    /// CP, used by Disciples of the Hand.
+    Cp(u32),
+}
+
+impl Resource {
+    /// The maximum value of the resource, whichever it is.
+    pub fn value(self) -> u32 {
+        match self {
+            Resource::Mp(value) | Resource::Gp(value) | Resource::Cp(value) => value,
+        }
+    }
+}
+
 /// Holds information about a profiles level in a particular class.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Attributes(HashMap<String, Attribute>);
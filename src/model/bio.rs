@@ -0,0 +1,105 @@
+use select::node::{Data, Node};
+
+use std::fmt;
+
+/// The free-text "Character Profile" a player has written about their character.
+///
+/// Line breaks are kept as `\n`, and HTML entities are already decoded.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Bio(String);
+
+impl Bio {
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        Bio(text.into())
+    }
+
+    /// The bio as plain text.
+    pub fn as_text(&self) -> &str {
+        &self.0
+    }
+
+    /// Iterates over the lines of the bio, including the empty ones.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.0.lines()
+    }
+
+    /// Renders the bio as markdown which displays the same as on the Lodestone.
+    ///
+    /// Characters markdown would give a meaning to are escaped, single
+    /// line breaks become hard breaks and empty lines separate paragraphs.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::with_capacity(self.0.len());
+        let mut previous_blank = true;
+
+        for (index, line) in self.lines().enumerate() {
+            let blank = line.trim().is_empty();
+            if index > 0 {
+                markdown.push_str(if blank || previous_blank { "\n" } else { "\\\n" });
+            }
+
+            markdown.push_str(&escape_markdown(line));
+            previous_blank = blank;
+        }
+
+        markdown
+    }
+
+    /// Parses the `character__selfintroduction` block of a profile,
+    /// which is empty for characters without a bio.
+    pub(crate) fn parse(node: &Node) -> Option<Self> {
+        let mut raw = String::new();
+        push_text(node, &mut raw);
+
+        let lines = raw.lines().map(str::trim).collect::<Vec<_>>();
+        let text = lines.join("\n").trim_matches('\n').to_string();
+
+        match text.is_empty() {
+            true => None,
+            false => Some(Bio(text)),
+        }
+    }
+}
+
+impl fmt::Display for Bio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Collects the text of a node, turning `<br>` into line breaks. Line breaks
+/// within the HTML source are not shown by browsers, so they are dropped.
+fn push_text(node: &Node, text: &mut String) {
+    for child in node.children() {
+        match child.data() {
+            Data::Text(content) => text.extend(content.chars().filter(|c| *c != '\n' && *c != '\r')),
+            Data::Element(..) if child.name() == Some("br") => text.push('\n'),
+            Data::Element(..) => push_text(&child, text),
+            Data::Comment(..) => {}
+        }
+    }
+}
+
+/// Escapes a single line of text so markdown shows it as it is.
+fn escape_markdown(line: &str) -> String {
+    let mut escaped = String::with_capacity(line.len());
+
+    for c in line.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '~') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    //  Headings, quotes, lists and ordered lists only start at the beginning of a line
+    let content = escaped.trim_start();
+    let indent = escaped.len() - content.len();
+    let number = content.chars().take_while(char::is_ascii_digit).count();
+
+    if content.starts_with(['#', '-', '+']) {
+        escaped.insert(indent, '\\');
+    } else if number > 0 && content[number..].starts_with(['.', ')']) {
+        escaped.insert(indent + number, '\\');
+    }
+
+    escaped
+}
@@ -0,0 +1,43 @@
+use thiserror::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Error)]
+#[error("Invalid city-state string '{0}'")]
+pub struct CityStateParseError(String);
+
+/// The city-states a character can start in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum CityState {
+    LimsaLominsa,
+    Gridania,
+    Uldah,
+}
+
+/// Case insensitive FromStr impl for city-states, accepting the
+/// names used on any of the Lodestones.
+impl FromStr for CityState {
+    type Err = CityStateParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.trim().to_uppercase() {
+            "LIMSA LOMINSA" | "リムサ・ロミンサ" => Ok(CityState::LimsaLominsa),
+            "GRIDANIA" | "グリダニア" => Ok(CityState::Gridania),
+            "UL'DAH" | "ウルダハ" => Ok(CityState::Uldah),
+            x => Err(CityStateParseError(x.into())),
+        }
+    }
+}
+
+/// Displays the city-state as shown on the English Lodestone.
+impl fmt::Display for CityState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let city_state = match *self {
+            CityState::LimsaLominsa => "Limsa Lominsa",
+            CityState::Gridania => "Gridania",
+            CityState::Uldah => "Ul'dah",
+        };
+
+        write!(f, "{}", city_state)
+    }
+}
@@ -1,8 +1,10 @@
-use failure::Fail;
+use thiserror::Error;
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Fail)]
-#[fail(display = "Invalid clan string '{}'", _0)]
+use crate::model::race::Race;
+
+#[derive(Clone, Debug, Error)]
+#[error("Invalid clan string '{0}'")]
 pub struct ClanParseError(String);
 
 /// Enumeration for the clans available in XIV.
@@ -34,27 +36,68 @@ pub enum Clan {
     Helions,
 }
 
+impl Clan {
+    /// The id used for this clan by the game and in Lodestone URLs.
+    pub fn id(self) -> u32 {
+        match self {
+            Clan::Midlander => 1,
+            Clan::Highlander => 2,
+            Clan::Wildwood => 3,
+            Clan::Duskwight => 4,
+            Clan::Plainsfolk => 5,
+            Clan::Dunesfolk => 6,
+            Clan::SeekerOfTheSun => 7,
+            Clan::KeeperOfTheMoon => 8,
+            Clan::SeaWolf => 9,
+            Clan::Hellsguard => 10,
+            Clan::Raen => 11,
+            Clan::Xaela => 12,
+            Clan::Helions => 13,
+            Clan::TheLost => 14,
+            Clan::Rava => 15,
+            Clan::Veena => 16,
+        }
+    }
+
+    /// The race this clan belongs to.
+    pub fn race(self) -> Race {
+        match self {
+            Clan::Midlander | Clan::Highlander => Race::Hyur,
+            Clan::Wildwood | Clan::Duskwight => Race::Elezen,
+            Clan::Plainsfolk | Clan::Dunesfolk => Race::Lalafell,
+            Clan::SeekerOfTheSun | Clan::KeeperOfTheMoon => Race::Miqote,
+            Clan::SeaWolf | Clan::Hellsguard => Race::Roegadyn,
+            Clan::Raen | Clan::Xaela => Race::Aura,
+            Clan::Helions | Clan::TheLost => Race::Hrothgar,
+            Clan::Rava | Clan::Veena => Race::Viera,
+        }
+    }
+}
+
+/// Case insensitive FromStr impl for clans, accepting the names
+/// used on the English, German, French and Japanese Lodestones.
 impl FromStr for Clan {
     type Err = ClanParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match &*s.to_uppercase() {
-            "XAELA" => Ok(Clan::Xaela),
-            "RAEN" => Ok(Clan::Raen),
-            "WILDWOOD" => Ok(Clan::Wildwood),
-            "DUSKWIGHT" => Ok(Clan::Duskwight),
-            "MIDLANDER" => Ok(Clan::Midlander),
-            "HIGHLANDER" => Ok(Clan::Highlander),
-            "DUNESFOLK" => Ok(Clan::Dunesfolk),
-            "PLAINSFOLK" => Ok(Clan::Plainsfolk),
-            "SEEKER OF THE SUN" => Ok(Clan::SeekerOfTheSun),
-            "KEEPER OF THE MOON" => Ok(Clan::KeeperOfTheMoon),
-            "SEA WOLF" => Ok(Clan::SeaWolf),
-            "HELLSGUARD" => Ok(Clan::Hellsguard),
-            "VEENA" => Ok(Clan::Veena),
-            "RAVA" => Ok(Clan::Rava),
-            "THE LOST" => Ok(Clan::TheLost),
-            "HELIONS" => Ok(Clan::Helions),
+            "XAELA" | "アウラ・ゼラ" => Ok(Clan::Xaela),
+            "RAEN" | "アウラ・レン" => Ok(Clan::Raen),
+            "WILDWOOD" | "ERLSCHATTEN" | "SYLVESTRE" | "フォレスター" => Ok(Clan::Wildwood),
+            "DUSKWIGHT" | "DUNKELALB" | "CRÉPUSCULAIRE" | "シェーダー" => Ok(Clan::Duskwight),
+            "MIDLANDER" | "WIESLÄNDER" | "HYUROIS" | "HYUROISE" | "ミッドランダー" => Ok(Clan::Midlander),
+            "HIGHLANDER" | "HOCHLÄNDER" | "HYURGOTH" | "ハイランダー" => Ok(Clan::Highlander),
+            "DUNESFOLK" | "SANDLING" | "PEUPLE DES DUNES" | "デューンフォーク" => Ok(Clan::Dunesfolk),
+            "PLAINSFOLK" | "HALMLING" | "PEUPLE DES PLAINES" | "プレーンフォーク" => Ok(Clan::Plainsfolk),
+            "SEEKER OF THE SUN" | "GOLDTATZE" | "TRIBU DU SOLEIL" | "サンシーカー" => Ok(Clan::SeekerOfTheSun),
+            "KEEPER OF THE MOON" | "MONDSTREUNER" | "TRIBU DE LA LUNE" | "ムーンキーパー" => Ok(Clan::KeeperOfTheMoon),
+            "SEA WOLF" | "SEEWOLF" | "CLAN DE LA MER" | "ゼーヴォルフ" => Ok(Clan::SeaWolf),
+            "HELLSGUARD" | "LOHENGARDE" | "CLAN DU FEU" | "ローエンガルデ" => Ok(Clan::Hellsguard),
+            "VEENA" | "ヴィナ・ヴィエラ" => Ok(Clan::Veena),
+            "RAVA" | "ラヴァ・ヴィエラ" => Ok(Clan::Rava),
+            //  The article isn't always part of the name of the Lost
+            "THE LOST" | "LOST" | "DIE VERLORENEN" | "VERLORENE" | "LES ÉGARÉS" | "ÉGARÉS" | "ロスト" => Ok(Clan::TheLost),
+            "HELIONS" | "HELIONEN" | "HÉLIONS" | "ヘリオン" => Ok(Clan::Helions),
             x => Err(ClanParseError(x.into())),
         }
     }
@@ -1,9 +1,9 @@
-use failure::Fail;
+use thiserror::Error;
 use std::collections::HashMap;
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Fail)]
-#[fail(display = "Invalid class type '{}'", _0)]
+#[derive(Clone, Debug, Error)]
+#[error("Invalid class type '{0}'")]
 pub struct ClassTypeParseError(String);
 
 /// Contains all the data for a class/job insofar as it pertains to a specific character
@@ -72,67 +72,203 @@ pub enum ClassType {
     Fisher,
 }
 
+impl ClassType {
+    /// The id used for this class by the game and in Lodestone URLs.
+    pub fn id(self) -> u32 {
+        match self {
+            ClassType::Gladiator => 1,
+            ClassType::Pugilist => 2,
+            ClassType::Marauder => 3,
+            ClassType::Lancer => 4,
+            ClassType::Archer => 5,
+            ClassType::Conjurer => 6,
+            ClassType::Thaumaturge => 7,
+            ClassType::Carpenter => 8,
+            ClassType::Blacksmith => 9,
+            ClassType::Armorer => 10,
+            ClassType::Goldsmith => 11,
+            ClassType::Leatherworker => 12,
+            ClassType::Weaver => 13,
+            ClassType::Alchemist => 14,
+            ClassType::Culinarian => 15,
+            ClassType::Miner => 16,
+            ClassType::Botanist => 17,
+            ClassType::Fisher => 18,
+            ClassType::Paladin => 19,
+            ClassType::Monk => 20,
+            ClassType::Warrior => 21,
+            ClassType::Dragoon => 22,
+            ClassType::Bard => 23,
+            ClassType::WhiteMage => 24,
+            ClassType::BlackMage => 25,
+            ClassType::Arcanist => 26,
+            ClassType::Summoner => 27,
+            ClassType::Scholar => 28,
+            ClassType::Rogue => 29,
+            ClassType::Ninja => 30,
+            ClassType::Machinist => 31,
+            ClassType::DarkKnight => 32,
+            ClassType::Astrologian => 33,
+            ClassType::Samurai => 34,
+            ClassType::RedMage => 35,
+            ClassType::BlueMage => 36,
+            ClassType::Gunbreaker => 37,
+            ClassType::Dancer => 38,
+            ClassType::Reaper => 39,
+            ClassType::Sage => 40,
+            ClassType::Viper => 41,
+            ClassType::Pictomancer => 42,
+        }
+    }
+}
+
+/// The full name of every class, as shown on each of the Lodestones.
+///
+/// German and French names come in both their masculine and feminine
+/// forms, as the Lodestone uses the form matching the character's gender.
+const NAMES: [(ClassType, &[&str]); 42] = [
+    //   Tank
+    (ClassType::Paladin, &["PALADIN", "PALADININ", "ナイト"]),
+    (ClassType::Gladiator, &["GLADIATOR", "GLADIATORIN", "GLADIATEUR", "GLADIATRICE", "剣術士"]),
+    (ClassType::Warrior, &["WARRIOR", "KRIEGER", "KRIEGERIN", "GUERRIER", "GUERRIÈRE", "戦士"]),
+    (ClassType::Marauder, &["MARAUDER", "MARODEUR", "MARODEURIN", "MARAUDEUR", "MARAUDEUSE", "斧術士"]),
+    (ClassType::DarkKnight, &["DARK KNIGHT", "DUNKELRITTER", "DUNKELRITTERIN", "CHEVALIER NOIR", "CHEVALIÈRE NOIRE", "暗黒騎士"]),
+    (ClassType::Gunbreaker, &["GUNBREAKER", "REVOLVERKLINGE", "PISTOSABREUR", "PISTOSABREUSE", "ガンブレイカー"]),
+    //   Healer
+    (ClassType::WhiteMage, &["WHITE MAGE", "WEISSMAGIER", "WEISSMAGIERIN", "MAGE BLANC", "MAGE BLANCHE", "白魔道士"]),
+    (ClassType::Conjurer, &["CONJURER", "DRUIDE", "DRUIDIN", "ÉLÉMENTALISTE", "幻術士"]),
+    (ClassType::Scholar, &["SCHOLAR", "GELEHRTER", "GELEHRTE", "ÉRUDIT", "ÉRUDITE", "学者"]),
+    (ClassType::Astrologian, &["ASTROLOGIAN", "ASTROLOGE", "ASTROLOGIN", "ASTROMANCIEN", "ASTROMANCIENNE", "占星術師"]),
+    (ClassType::Sage, &["SAGE", "WEISER", "WEISE", "賢者"]),
+    //   Melee
+    (ClassType::Monk, &["MONK", "MÖNCH", "MÖNCHIN", "MOINE", "モンク"]),
+    (ClassType::Pugilist, &["PUGILIST", "FAUSTKÄMPFER", "FAUSTKÄMPFERIN", "PUGILISTE", "格闘士"]),
+    (ClassType::Dragoon, &["DRAGOON", "CHEVALIER DRAGON", "CHEVALIÈRE DRAGON", "竜騎士"]),
+    (ClassType::Lancer, &["LANCER", "PIKENIER", "PIKENIERIN", "MAÎTRE D'HAST", "MAÎTRESSE D'HAST", "槍術士"]),
+    (ClassType::Ninja, &["NINJA", "忍者"]),
+    (ClassType::Rogue, &["ROGUE", "SCHURKE", "SCHURKIN", "SURINEUR", "SURINEUSE", "双剣士"]),
+    (ClassType::Samurai, &["SAMURAI", "SAMOURAÏ", "侍"]),
+    (ClassType::Reaper, &["REAPER", "SCHNITTER", "SCHNITTERIN", "FAUCHEUR", "FAUCHEUSE", "リーパー"]),
+    (ClassType::Viper, &["VIPER", "RÔDEUR VIPÈRE", "RÔDEUSE VIPÈRE", "ヴァイパー"]),
+    //   Phys Range
+    (ClassType::Bard, &["BARD", "BARDE", "BARDIN", "吟遊詩人"]),
+    (ClassType::Archer, &["ARCHER", "WALDLÄUFER", "WALDLÄUFERIN", "ARCHÈRE", "弓術士"]),
+    (ClassType::Machinist, &["MACHINIST", "MASCHINIST", "MASCHINISTIN", "MACHINISTE", "機工士"]),
+    (ClassType::Dancer, &["DANCER", "TÄNZER", "TÄNZERIN", "DANSEUR", "DANSEUSE", "踊り子"]),
+    //   Caster
+    (ClassType::BlackMage, &["BLACK MAGE", "SCHWARZMAGIER", "SCHWARZMAGIERIN", "MAGE NOIR", "MAGE NOIRE", "黒魔道士"]),
+    (ClassType::Thaumaturge, &["THAUMATURGE", "THAUMATURG", "THAUMATURGIN", "OCCULTISTE", "呪術士"]),
+    (ClassType::Summoner, &["SUMMONER", "BESCHWÖRER", "BESCHWÖRERIN", "INVOCATEUR", "INVOCATRICE", "召喚士"]),
+    (ClassType::Arcanist, &["ARCANIST", "HERMETIKER", "HERMETIKERIN", "ARCANISTE", "巴術士"]),
+    (ClassType::RedMage, &["RED MAGE", "ROTMAGIER", "ROTMAGIERIN", "MAGE ROUGE", "赤魔道士"]),
+    (ClassType::Pictomancer, &["PICTOMANCER", "PIKTOMANT", "PIKTOMANTIN", "PICTOMANCIEN", "PICTOMANCIENNE", "ピクトマンサー"]),
+    (ClassType::BlueMage, &["BLUE MAGE", "BLAUMAGIER", "BLAUMAGIERIN", "MAGE BLEU", "MAGE BLEUE", "青魔道士"]),
+    //   DoH
+    (ClassType::Carpenter, &["CARPENTER", "ZIMMERER", "ZIMMERIN", "MENUISIER", "MENUISIÈRE", "木工師"]),
+    (ClassType::Blacksmith, &["BLACKSMITH", "GROBSCHMIED", "GROBSCHMIEDIN", "FORGERON", "FORGERONNE", "鍛冶師"]),
+    (ClassType::Armorer, &["ARMORER", "PLATTNER", "PLATTNERIN", "ARMURIER", "ARMURIÈRE", "甲冑師"]),
+    (ClassType::Goldsmith, &["GOLDSMITH", "GOLDSCHMIED", "GOLDSCHMIEDIN", "ORFÈVRE", "彫金師"]),
+    (ClassType::Leatherworker, &["LEATHERWORKER", "GERBER", "GERBERIN", "TANNEUR", "TANNEUSE", "革細工師"]),
+    (ClassType::Weaver, &["WEAVER", "WEBER", "WEBERIN", "COUTURIER", "COUTURIÈRE", "裁縫師"]),
+    (ClassType::Alchemist, &["ALCHEMIST", "ALCHEMISTIN", "ALCHIMISTE", "錬金術師"]),
+    (ClassType::Culinarian, &["CULINARIAN", "GOURMET", "GOURMETIN", "CUISINIER", "CUISINIÈRE", "調理師"]),
+    //   DoL
+    (ClassType::Miner, &["MINER", "MINENARBEITER", "MINENARBEITERIN", "MINEUR", "MINEUSE", "採掘師"]),
+    (ClassType::Botanist, &["BOTANIST", "GÄRTNER", "GÄRTNERIN", "BOTANISTE", "園芸師"]),
+    (ClassType::Fisher, &["FISHER", "FISCHER", "FISCHERIN", "PÊCHEUR", "PÊCHEUSE", "漁師"]),
+];
+
+impl ClassType {
+    /// Finds the class whose name appears within a longer text, such as
+    /// `Soul of the Black Mage` or `Arme à deux mains d'occultiste`.
+    ///
+    /// Names are looked for in every language, and the longest one found
+    /// wins, so that e.g. `Thaumaturgenwaffe` is still understood.
+    pub(crate) fn find_in(text: &str) -> Option<Self> {
+        let upper = text.to_uppercase();
+
+        NAMES.iter()
+            .flat_map(|(class, names)| names.iter().map(move |name| (*class, name)))
+            .filter(|(_, name)| upper.contains(*name))
+            .max_by_key(|(_, name)| name.chars().count())
+            .map(|(class, _)| class)
+    }
+}
+
 /// Takes a string from lodestone and converts it to a ClassType.
 /// Can take either the full name, or its common abbreviation as
 /// shown on gear and the conversion is case insensitive.
 /// 
 /// For example, `paladin` and `PLD` will both convert to 
 /// `ClassType::Paladin`
+///
+/// Names from the German, French and Japanese Lodestones are
+/// also accepted, so `Krieger` and `戦士` are `ClassType::Warrior`.
 impl FromStr for ClassType {
     type Err = ClassTypeParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &*s.to_uppercase() {
+        let upper = s.to_uppercase();
+
+        match &*upper {
             //   Tank
-            "PALADIN"       | "PLD" => Ok(ClassType::Paladin),
-            "GLADIATOR"     | "GLD" => Ok(ClassType::Gladiator),
-            "WARRIOR"       | "WAR" => Ok(ClassType::Warrior),
-            "MARAUDER"      | "MRD" => Ok(ClassType::Marauder),
-            "DARK KNIGHT"   | "DRK" => Ok(ClassType::DarkKnight),
-            "GUNBREAKER"    | "GNB" => Ok(ClassType::Gunbreaker),
+            "PLD" => Ok(ClassType::Paladin),
+            "GLD" => Ok(ClassType::Gladiator),
+            "WAR" => Ok(ClassType::Warrior),
+            "MRD" => Ok(ClassType::Marauder),
+            "DRK" => Ok(ClassType::DarkKnight),
+            "GNB" => Ok(ClassType::Gunbreaker),
             //   Healer
-            "WHITE MAGE"    | "WHM" => Ok(ClassType::WhiteMage),
-            "CONJURER"      | "CNJ" => Ok(ClassType::Conjurer),
-            "SCHOLAR"       | "SCH" => Ok(ClassType::Scholar),
-            "ASTROLOGIAN"   | "AST" => Ok(ClassType::Astrologian),
-            "SAGE"          | "SGE" => Ok(ClassType::Sage),
+            "WHM" => Ok(ClassType::WhiteMage),
+            "CNJ" => Ok(ClassType::Conjurer),
+            "SCH" => Ok(ClassType::Scholar),
+            "AST" => Ok(ClassType::Astrologian),
+            "SGE" => Ok(ClassType::Sage),
             //   Melee
-            "MONK"          | "MNK" => Ok(ClassType::Monk),
-            "PUGILIST"      | "PUG" => Ok(ClassType::Pugilist),
-            "DRAGOON"       | "DRG" => Ok(ClassType::Dragoon),
-            "LANCER"        | "LNC" => Ok(ClassType::Lancer),
-            "NINJA"         | "NIN" => Ok(ClassType::Ninja),
-            "ROGUE"         | "ROG" => Ok(ClassType::Rogue),
-            "SAMURAI"       | "SAM" => Ok(ClassType::Samurai),
-            "REAPER"        | "RPR" => Ok(ClassType::Reaper),
-            "VIPER"         | "VPR" => Ok(ClassType::Viper),
+            "MNK" => Ok(ClassType::Monk),
+            "PUG" => Ok(ClassType::Pugilist),
+            "DRG" => Ok(ClassType::Dragoon),
+            "LNC" => Ok(ClassType::Lancer),
+            "NIN" => Ok(ClassType::Ninja),
+            "ROG" => Ok(ClassType::Rogue),
+            "SAM" => Ok(ClassType::Samurai),
+            "RPR" => Ok(ClassType::Reaper),
+            "VPR" => Ok(ClassType::Viper),
             //   Phys Range
-            "BARD"          | "BRD" => Ok(ClassType::Bard),
-            "ARCHER"        | "ARC" => Ok(ClassType::Archer),
-            "MACHINIST"     | "MCH" => Ok(ClassType::Machinist),
-            "DANCER"        | "DNC" => Ok(ClassType::Dancer),
+            "BRD" => Ok(ClassType::Bard),
+            "ARC" => Ok(ClassType::Archer),
+            "MCH" => Ok(ClassType::Machinist),
+            "DNC" => Ok(ClassType::Dancer),
             //   Caster
-            "BLACK MAGE"    | "BLM" => Ok(ClassType::BlackMage),
-            "THAUMATURGE"   | "THM" => Ok(ClassType::Thaumaturge),
-            "SUMMONER"      | "SMN" => Ok(ClassType::Summoner),
-            "ARCANIST"      | "ACN" => Ok(ClassType::Arcanist),
-            "RED MAGE"      | "RDM" => Ok(ClassType::RedMage),
-            "PICTOMANCER"   | "PCT" => Ok(ClassType::Pictomancer),
-            "BLUE MAGE" | "BLUE MAGE (LIMITED JOB)" | "BLU" => Ok(ClassType::BlueMage),
+            "BLM" => Ok(ClassType::BlackMage),
+            "THM" => Ok(ClassType::Thaumaturge),
+            "SMN" => Ok(ClassType::Summoner),
+            "ACN" => Ok(ClassType::Arcanist),
+            "RDM" => Ok(ClassType::RedMage),
+            "PCT" => Ok(ClassType::Pictomancer),
+            "BLU" => Ok(ClassType::BlueMage),
             //   DoH
-            "CARPENTER"     | "CRP" => Ok(ClassType::Carpenter),
-            "BLACKSMITH"    | "BSM" => Ok(ClassType::Blacksmith),
-            "ARMORER"       | "ARM" => Ok(ClassType::Armorer),
-            "GOLDSMITH"     | "GSM" => Ok(ClassType::Goldsmith),
-            "LEATHERWORKER" | "LTW" => Ok(ClassType::Leatherworker),
-            "WEAVER"        | "WVR" => Ok(ClassType::Weaver),
-            "ALCHEMIST"     | "ALC" => Ok(ClassType::Alchemist),
-            "CULINARIAN"    | "CUL" => Ok(ClassType::Culinarian),
+            "CRP" => Ok(ClassType::Carpenter),
+            "BSM" => Ok(ClassType::Blacksmith),
+            "ARM" => Ok(ClassType::Armorer),
+            "GSM" => Ok(ClassType::Goldsmith),
+            "LTW" => Ok(ClassType::Leatherworker),
+            "WVR" => Ok(ClassType::Weaver),
+            "ALC" => Ok(ClassType::Alchemist),
+            "CUL" => Ok(ClassType::Culinarian),
             //   DoL
-            "MINER"         | "MIN" => Ok(ClassType::Miner),
-            "BOTANIST"      | "BTN" => Ok(ClassType::Botanist),
-            "FISHER"        | "FSH" => Ok(ClassType::Fisher),
-            x => Err(ClassTypeParseError(x.into())),
+            "MIN" => Ok(ClassType::Miner),
+            "BTN" => Ok(ClassType::Botanist),
+            "FSH" => Ok(ClassType::Fisher),
+            _ => {
+                //  Limited jobs carry a suffix such as "(Limited Job)" or "（リミテッドジョブ）"
+                let name = upper.split(['(', '（']).next().unwrap_or(&upper).trim();
+
+                NAMES.iter()
+                    .find(|(_, names)| names.contains(&name))
+                    .map(|(class, _)| *class)
+                    .ok_or_else(|| ClassTypeParseError(upper.clone()))
+            }
         }
     }
 }
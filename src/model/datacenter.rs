@@ -1,9 +1,12 @@
-use failure::Fail;
+use thiserror::Error;
 use std::fmt;
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Fail)]
-#[fail(display = "Invalid datacenter string '{}'", _0)]
+use crate::model::region::Region;
+use crate::model::server::Server;
+
+#[derive(Clone, Debug, Error)]
+#[error("Invalid datacenter string '{0}'")]
 pub struct DatacenterParseError(String);
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -18,6 +21,74 @@ pub enum Datacenter {
     Primal,
     Materia,
     Shadow,
+    Dynamis,
+    Meteor,
+}
+
+impl Datacenter {
+    /// The region this datacenter is located in.
+    pub fn region(self) -> Region {
+        match self {
+            Datacenter::Aether | Datacenter::Primal | Datacenter::Crystal | Datacenter::Dynamis => Region::NA,
+            Datacenter::Chaos | Datacenter::Light | Datacenter::Shadow => Region::EU,
+            Datacenter::Materia => Region::OCE,
+            Datacenter::Elemental | Datacenter::Gaia | Datacenter::Mana | Datacenter::Meteor => Region::JP,
+        }
+    }
+
+    /// The servers that are part of this datacenter, in the
+    /// order they are listed on the world status page.
+    pub fn servers(self) -> &'static [Server] {
+        match self {
+            Datacenter::Aether => &[
+                Server::Adamantoise, Server::Cactuar, Server::Faerie, Server::Gilgamesh,
+                Server::Jenova, Server::Midgardsormr, Server::Sargatanas, Server::Siren,
+            ],
+            Datacenter::Primal => &[
+                Server::Behemoth, Server::Excalibur, Server::Exodus, Server::Famfrit,
+                Server::Hyperion, Server::Lamia, Server::Leviathan, Server::Ultros,
+            ],
+            Datacenter::Crystal => &[
+                Server::Balmung, Server::Brynhildr, Server::Coeurl, Server::Diabolos,
+                Server::Goblin, Server::Malboro, Server::Mateus, Server::Zalera,
+            ],
+            Datacenter::Dynamis => &[
+                Server::Halicarnassus, Server::Maduin, Server::Marilith, Server::Seraph,
+                Server::Cuchulainn, Server::Kraken, Server::Rafflesia, Server::Golem,
+            ],
+            Datacenter::Chaos => &[
+                Server::Cerberus, Server::Louisoix, Server::Moogle, Server::Omega,
+                Server::Phantom, Server::Ragnarok, Server::Sagittarius, Server::Spriggan,
+            ],
+            Datacenter::Light => &[
+                Server::Alpha, Server::Lich, Server::Odin, Server::Phoenix,
+                Server::Raiden, Server::Shiva, Server::Twintania, Server::Zodiark,
+            ],
+            Datacenter::Shadow => &[
+                Server::Innocence, Server::Pixie, Server::Titania, Server::Tycoon,
+            ],
+            Datacenter::Materia => &[
+                Server::Bismarck, Server::Ravana, Server::Sephirot, Server::Sophia,
+                Server::Zurvan,
+            ],
+            Datacenter::Elemental => &[
+                Server::Aegis, Server::Atomos, Server::Carbuncle, Server::Garuda,
+                Server::Gungnir, Server::Kujata, Server::Tonberry, Server::Typhon,
+            ],
+            Datacenter::Gaia => &[
+                Server::Alexander, Server::Bahamut, Server::Durandal, Server::Fenrir,
+                Server::Ifrit, Server::Ridill, Server::Tiamat, Server::Ultima,
+            ],
+            Datacenter::Mana => &[
+                Server::Anima, Server::Asura, Server::Chocobo, Server::Hades,
+                Server::Ixion, Server::Masamune, Server::Pandaemonium, Server::Titan,
+            ],
+            Datacenter::Meteor => &[
+                Server::Belias, Server::Mandragora, Server::Ramuh, Server::Shinryu,
+                Server::Unicorn, Server::Valefor, Server::Yojimbo, Server::Zeromus,
+            ],
+        }
+    }
 }
 
 /// Case insensitive FromStr impl for datacenters.
@@ -36,6 +107,8 @@ impl FromStr for Datacenter {
             "PRIMAL" => Ok(Datacenter::Primal),
             "MATERIA" => Ok(Datacenter::Materia),
             "SHADOW" => Ok(Datacenter::Shadow),
+            "DYNAMIS" => Ok(Datacenter::Dynamis),
+            "METEOR" => Ok(Datacenter::Meteor),
             x => Err(DatacenterParseError(x.into())),
         }
     }
@@ -54,6 +127,8 @@ impl fmt::Display for Datacenter {
             Datacenter::Primal => "Primal",
             Datacenter::Materia => "Materia",
             Datacenter::Shadow => "Shadow",
+            Datacenter::Dynamis => "Dynamis",
+            Datacenter::Meteor => "Meteor",
         };
 
         write!(f, "{}", datacenter)
@@ -0,0 +1,68 @@
+use thiserror::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::model::language::Language;
+
+#[derive(Clone, Debug, Error)]
+#[error("Invalid domain string '{0}'")]
+pub struct DomainParseError(String);
+
+/// The regional versions of the Lodestone website.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Domain {
+    #[default]
+    NA,
+    EU,
+    FR,
+    DE,
+    JP,
+}
+
+impl Domain {
+    /// The language pages are served in on this Lodestone.
+    pub fn language(self) -> Language {
+        match self {
+            Domain::NA | Domain::EU => Language::English,
+            Domain::FR => Language::French,
+            Domain::DE => Language::German,
+            Domain::JP => Language::Japanese,
+        }
+    }
+
+    /// The root URL of this Lodestone, e.g. `https://na.finalfantasyxiv.com/lodestone/`
+    pub fn base_url(self) -> String {
+        format!("https://{}.finalfantasyxiv.com/lodestone/", self)
+    }
+}
+
+/// Case insensitive FromStr impl for domains.
+impl FromStr for Domain {
+    type Err = DomainParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_uppercase() {
+            "NA" => Ok(Domain::NA),
+            "EU" => Ok(Domain::EU),
+            "FR" => Ok(Domain::FR),
+            "DE" => Ok(Domain::DE),
+            "JP" => Ok(Domain::JP),
+            x => Err(DomainParseError(x.into())),
+        }
+    }
+}
+
+/// Displays the subdomain used for this Lodestone.
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let domain = match *self {
+            Domain::NA => "na",
+            Domain::EU => "eu",
+            Domain::FR => "fr",
+            Domain::DE => "de",
+            Domain::JP => "jp",
+        };
+
+        write!(f, "{}", domain)
+    }
+}
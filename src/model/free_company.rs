@@ -0,0 +1,49 @@
+use select::document::Document;
+use select::predicate::{Class, Name, Predicate};
+
+use crate::model::domain::Domain;
+
+/// The Free Company a character belongs to, as linked from their profile.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FreeCompanyRef {
+    /// The id of the Free Company on the Lodestone.
+    pub id: u64,
+    /// The name of the Free Company.
+    pub name: String,
+    /// The URLs of the images the crest is made of, from the
+    /// background to the foreground, to be drawn on top of each other.
+    pub crest: Vec<String>,
+}
+
+impl FreeCompanyRef {
+    /// The URL of the Free Company's page on a given Lodestone.
+    pub fn url(&self, domain: Domain) -> String {
+        format!("{}freecompany/{}/", domain.base_url(), self.id)
+    }
+
+    /// Parses the Free Company shown on the main page of a character,
+    /// which is absent for characters who aren't in one.
+    pub(crate) fn parse(doc: &Document) -> Option<Self> {
+        let link = doc.find(Class("character__freecompany__name").descendant(Name("a"))).next()?;
+
+        Some(FreeCompanyRef {
+            id: parse_id(link.attr("href")?)?,
+            name: link.text().trim().to_string(),
+            crest: doc
+                .find(Class("character__freecompany__crest__image").descendant(Name("img")))
+                .filter_map(|img| img.attr("src"))
+                .map(|src| src.to_string())
+                .collect(),
+        })
+    }
+}
+
+/// Takes the id out of a link such as `/lodestone/freecompany/9229283011365743624/`.
+fn parse_id(href: &str) -> Option<u64> {
+    href.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|_| href.contains("/freecompany/"))?
+        .parse()
+        .ok()
+}
@@ -1,8 +1,8 @@
-use failure::Fail;
+use thiserror::Error;
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Fail)]
-#[fail(display = "Invalid grand company string '{}'", _0)]
+#[derive(Clone, Debug, Error)]
+#[error("Invalid grand company string '{0}'")]
 pub struct GrandCompanyParseError(String);
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -13,16 +13,192 @@ pub enum GrandCompany {
     Unaffiliated,
 }
 
+/// Takes a grand company as shown on any of the Lodestones, such as
+/// `Order of the Twin Adder` or `Les Immortels`.
 impl FromStr for GrandCompany {
     type Err = GrandCompanyParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &*s.to_uppercase() {
-            "MAELSTROM" => Ok(GrandCompany::Maelstrom),
-            "ORDER OF THE TWIN ADDER" | "TWIN ADDER" => Ok(GrandCompany::TwinAdder),
-            "IMMORTAL FLAMES" => Ok(GrandCompany::ImmortalFlames),
-            "" | "NONE" | "UNAFFILIATED" => Ok(GrandCompany::Unaffiliated),
-            x => Err(GrandCompanyParseError(x.into())),
+        let upper = s.trim().to_uppercase();
+        if matches!(&*upper, "" | "NONE" | "UNAFFILIATED") {
+            return Ok(GrandCompany::Unaffiliated);
         }
+
+        let companies: [(GrandCompany, &[&str]); 3] = [
+            (GrandCompany::Maelstrom, &["MAELSTROM", "MAHLSTROM", "黒渦団"]),
+            (GrandCompany::TwinAdder, &["TWIN ADDER", "MORGENVIPER", "DEUX VIPÈRES", "双蛇党"]),
+            (GrandCompany::ImmortalFlames, &["IMMORTAL FLAMES", "UNSTERBLICHEN", "IMMORTELS", "不滅隊"]),
+        ];
+
+        let company = companies
+            .iter()
+            .find(|(_, names)| names.iter().any(|name| upper.contains(name)))
+            .map(|(company, _)| *company);
+
+        company.ok_or(GrandCompanyParseError(upper))
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Debug, Error)]
+#[error("Invalid grand company rank string '{0}'")]
+pub struct GrandCompanyRankParseError(String);
+
+/// The rank of a character within their grand company.
+///
+/// Every company uses the same ranks, but names them after itself,
+/// e.g. `Storm Captain`, `Serpent Captain` and `Flame Captain`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum GrandCompanyRank {
+    PrivateThirdClass,
+    PrivateSecondClass,
+    PrivateFirstClass,
+    Corporal,
+    SergeantThirdClass,
+    SergeantSecondClass,
+    SergeantFirstClass,
+    ChiefSergeant,
+    SecondLieutenant,
+    FirstLieutenant,
+    Captain,
+}
+
+impl GrandCompany {
+    /// The word the company puts in the name of its ranks.
+    fn rank_prefix(self) -> Option<&'static str> {
+        match self {
+            GrandCompany::Maelstrom => Some("Storm"),
+            GrandCompany::TwinAdder => Some("Serpent"),
+            GrandCompany::ImmortalFlames => Some("Flame"),
+            GrandCompany::Unaffiliated => None,
+        }
+    }
+}
+
+/// How ranks are worded on the German, French and Japanese Lodestones,
+/// leaving out the part naming the company. A rank matches when its name
+/// contains every one of the words, and the first match wins, so more
+/// specific ranks come before the ones whose words they contain.
+const LOCALIZED_RANKS: [(GrandCompanyRank, &[&str]); 33] = [
+    (GrandCompanyRank::Captain, &["HAUPTMANN"]),
+    (GrandCompanyRank::Captain, &["CAPITAINE"]),
+    (GrandCompanyRank::Captain, &["大", "尉"]),
+    (GrandCompanyRank::FirstLieutenant, &["OBERLEUTNANT"]),
+    (GrandCompanyRank::FirstLieutenant, &["中", "尉"]),
+    (GrandCompanyRank::SecondLieutenant, &["LEUTNANT"]),
+    (GrandCompanyRank::SecondLieutenant, &["SOUS-LIEUTENANT"]),
+    (GrandCompanyRank::SecondLieutenant, &["少", "尉"]),
+    (GrandCompanyRank::FirstLieutenant, &["LIEUTENANT"]),
+    (GrandCompanyRank::ChiefSergeant, &["OBERFELDWEBEL"]),
+    (GrandCompanyRank::ChiefSergeant, &["SERGENT-CHEF"]),
+    (GrandCompanyRank::ChiefSergeant, &["曹長"]),
+    (GrandCompanyRank::SergeantFirstClass, &["FELDWEBEL", "1. KLASSE"]),
+    (GrandCompanyRank::SergeantFirstClass, &["SERGENT", "1RE CLASSE"]),
+    (GrandCompanyRank::SergeantFirstClass, &["一等", "曹"]),
+    (GrandCompanyRank::SergeantSecondClass, &["FELDWEBEL", "2. KLASSE"]),
+    (GrandCompanyRank::SergeantSecondClass, &["SERGENT", "2E CLASSE"]),
+    (GrandCompanyRank::SergeantSecondClass, &["二等", "曹"]),
+    (GrandCompanyRank::SergeantThirdClass, &["FELDWEBEL", "3. KLASSE"]),
+    (GrandCompanyRank::SergeantThirdClass, &["SERGENT", "3E CLASSE"]),
+    (GrandCompanyRank::SergeantThirdClass, &["三等", "曹"]),
+    (GrandCompanyRank::Corporal, &["KORPORAL"]),
+    (GrandCompanyRank::Corporal, &["CAPORAL"]),
+    (GrandCompanyRank::Corporal, &["兵長"]),
+    (GrandCompanyRank::PrivateFirstClass, &["GEFREITER", "1. KLASSE"]),
+    (GrandCompanyRank::PrivateFirstClass, &["SOLDAT", "1RE CLASSE"]),
+    (GrandCompanyRank::PrivateFirstClass, &["上等", "兵"]),
+    (GrandCompanyRank::PrivateSecondClass, &["GEFREITER", "2. KLASSE"]),
+    (GrandCompanyRank::PrivateSecondClass, &["SOLDAT", "2E CLASSE"]),
+    (GrandCompanyRank::PrivateSecondClass, &["一等", "兵"]),
+    (GrandCompanyRank::PrivateThirdClass, &["GEFREITER", "3. KLASSE"]),
+    (GrandCompanyRank::PrivateThirdClass, &["SOLDAT", "3E CLASSE"]),
+    (GrandCompanyRank::PrivateThirdClass, &["二等", "兵"]),
+];
+
+impl GrandCompanyRank {
+    /// Reads the name of a rank as given by a specific company,
+    /// such as `Second Storm Lieutenant` for the Maelstrom.
+    ///
+    /// Names from the German, French and Japanese Lodestones are also
+    /// understood, e.g. `一等蛇兵` is a `PrivateSecondClass` of the Twin Adder.
+    pub fn from_name(company: GrandCompany, name: &str) -> Result<Self, GrandCompanyRankParseError> {
+        let prefix = company.rank_prefix()
+            .ok_or_else(|| GrandCompanyRankParseError(name.into()))?;
+
+        Self::from_english_name(name, &[prefix])
+            .or_else(|| {
+                //  An English rank of another company must not be read as a French one
+                let others = ["Storm", "Serpent", "Flame"];
+                match Self::from_english_name(name, &others) {
+                    Some(_) => None,
+                    None => Self::from_localized_name(company, name),
+                }
+            })
+            .ok_or_else(|| GrandCompanyRankParseError(name.into()))
+    }
+
+    /// Reads an English rank name once the given company words are left out.
+    fn from_english_name(name: &str, prefixes: &[&str]) -> Option<Self> {
+        let generic = name
+            .split_whitespace()
+            .filter(|word| !prefixes.iter().any(|prefix| word.eq_ignore_ascii_case(prefix)))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match &*generic.to_uppercase() {
+            "PRIVATE THIRD CLASS" => Some(GrandCompanyRank::PrivateThirdClass),
+            "PRIVATE SECOND CLASS" => Some(GrandCompanyRank::PrivateSecondClass),
+            "PRIVATE FIRST CLASS" => Some(GrandCompanyRank::PrivateFirstClass),
+            "CORPORAL" => Some(GrandCompanyRank::Corporal),
+            "SERGEANT THIRD CLASS" => Some(GrandCompanyRank::SergeantThirdClass),
+            "SERGEANT SECOND CLASS" => Some(GrandCompanyRank::SergeantSecondClass),
+            "SERGEANT FIRST CLASS" => Some(GrandCompanyRank::SergeantFirstClass),
+            "CHIEF SERGEANT" => Some(GrandCompanyRank::ChiefSergeant),
+            "SECOND LIEUTENANT" => Some(GrandCompanyRank::SecondLieutenant),
+            "FIRST LIEUTENANT" => Some(GrandCompanyRank::FirstLieutenant),
+            "CAPTAIN" => Some(GrandCompanyRank::Captain),
+            _ => None,
+        }
+    }
+
+    /// Reads a rank name from the German, French or Japanese Lodestone.
+    fn from_localized_name(company: GrandCompany, name: &str) -> Option<Self> {
+        let upper = name.trim().to_uppercase();
+
+        //  Japanese ranks name the company with a single character, e.g. 甲 in 大甲尉
+        let company_mark = match company {
+            GrandCompany::Maelstrom => '甲',
+            GrandCompany::TwinAdder => '蛇',
+            GrandCompany::ImmortalFlames => '闘',
+            GrandCompany::Unaffiliated => return None,
+        };
+        let japanese = upper.chars().any(|c| matches!(c, '甲' | '蛇' | '闘'));
+        if japanese && !upper.contains(company_mark) {
+            return None;
+        }
+
+        LOCALIZED_RANKS
+            .iter()
+            .find(|(_, words)| words.iter().all(|word| upper.contains(word)))
+            .map(|(rank, _)| *rank)
+    }
+
+    /// The name a specific company gives to the rank, or `None`
+    /// for characters who are not part of any company.
+    pub fn name(self, company: GrandCompany) -> Option<String> {
+        let prefix = company.rank_prefix()?;
+
+        Some(match self {
+            GrandCompanyRank::PrivateThirdClass => format!("{} Private Third Class", prefix),
+            GrandCompanyRank::PrivateSecondClass => format!("{} Private Second Class", prefix),
+            GrandCompanyRank::PrivateFirstClass => format!("{} Private First Class", prefix),
+            GrandCompanyRank::Corporal => format!("{} Corporal", prefix),
+            GrandCompanyRank::SergeantThirdClass => format!("{} Sergeant Third Class", prefix),
+            GrandCompanyRank::SergeantSecondClass => format!("{} Sergeant Second Class", prefix),
+            GrandCompanyRank::SergeantFirstClass => format!("{} Sergeant First Class", prefix),
+            GrandCompanyRank::ChiefSergeant => format!("Chief {} Sergeant", prefix),
+            GrandCompanyRank::SecondLieutenant => format!("Second {} Lieutenant", prefix),
+            GrandCompanyRank::FirstLieutenant => format!("First {} Lieutenant", prefix),
+            GrandCompanyRank::Captain => format!("{} Captain", prefix),
+        })
+    }
+}
@@ -0,0 +1,218 @@
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Class, Name, Predicate};
+
+use std::collections::BTreeMap;
+
+use crate::error::LodestoneError;
+use crate::model::class::ClassType;
+use crate::model::util::ensure_node;
+
+/// The high quality symbol the Lodestone appends to item names.
+const HQ_SYMBOL: char = '\u{E03C}';
+
+/// Where a piece of gear is equipped.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum GearSlot {
+    MainHand,
+    OffHand,
+    Head,
+    Body,
+    Hands,
+    Legs,
+    Feet,
+    Earrings,
+    Necklace,
+    Bracelets,
+    Ring1,
+    Ring2,
+    SoulCrystal,
+}
+
+impl GearSlot {
+    /// Every slot, in the order the Lodestone numbers them.
+    const ALL: [(u8, GearSlot); 13] = [
+        (0, GearSlot::MainHand),
+        (1, GearSlot::OffHand),
+        (2, GearSlot::Head),
+        (3, GearSlot::Body),
+        (4, GearSlot::Hands),
+        //  5 used to be the waist, which was removed from the game
+        (6, GearSlot::Legs),
+        (7, GearSlot::Feet),
+        (8, GearSlot::Earrings),
+        (9, GearSlot::Necklace),
+        (10, GearSlot::Bracelets),
+        (11, GearSlot::Ring1),
+        (12, GearSlot::Ring2),
+        (13, GearSlot::SoulCrystal),
+    ];
+}
+
+/// The item a piece of gear is glamoured to look like.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Glamour {
+    pub name: String,
+    /// The id of the item in the Lodestone's Eorzea Database.
+    pub item_id: Option<String>,
+}
+
+/// A piece of gear a character has equipped.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Gear {
+    pub slot: GearSlot,
+    pub name: String,
+    /// What kind of item it is, e.g. `Two-handed Thaumaturge's Arm`.
+    pub category: String,
+    /// The id of the item in the Lodestone's Eorzea Database.
+    pub item_id: Option<String>,
+    pub item_level: u16,
+    /// Whether the item is high quality.
+    pub hq: bool,
+    /// The names of the materia melded into the item.
+    pub materia: Vec<String>,
+    pub glamour: Option<Glamour>,
+    /// The name of the dye applied to the item.
+    pub dye: Option<String>,
+}
+
+impl Gear {
+    fn parse(slot: GearSlot, item: &Node) -> Result<Self, LodestoneError> {
+        let name = ensure_node!(item, Class("db-tooltip__item__name")).text();
+        let hq = name.contains(HQ_SYMBOL);
+        let level = ensure_node!(item, Class("db-tooltip__item__level")).text();
+
+        Ok(Gear {
+            slot,
+            name: name.replace(HQ_SYMBOL, "").trim().to_string(),
+            category: item
+                .find(Class("db-tooltip__item__category"))
+                .next()
+                .map(|category| category.text().trim().to_string())
+                .unwrap_or_default(),
+            item_id: item
+                .find(Class("db-tooltip__bt_item_detail").descendant(Name("a")))
+                .next()
+                .and_then(|link| item_id(link.attr("href")?)),
+            item_level: level
+                .split_whitespace()
+                .last()
+                .unwrap_or_default()
+                .parse()?,
+            hq,
+            materia: item
+                .find(Class("db-tooltip__materia__txt"))
+                .map(|materia| materia.children().next().map(|text| text.text()).unwrap_or_default().trim().to_string())
+                .filter(|materia| !materia.is_empty())
+                .collect(),
+            glamour: item
+                .find(Class("db-tooltip__item__mirage").descendant(Name("p")))
+                .next()
+                .map(|mirage| Glamour {
+                    name: mirage.text().trim().to_string(),
+                    item_id: mirage
+                        .find(Name("a"))
+                        .next()
+                        .and_then(|link| item_id(link.attr("href")?)),
+                }),
+            dye: item
+                .find(Class("stain"))
+                .next()
+                .map(|stain| stain.text().trim().to_string())
+                .filter(|stain| !stain.is_empty()),
+        })
+    }
+}
+
+/// Holds the gear a character has equipped, by slot.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Equipment(BTreeMap<GearSlot, Gear>);
+
+impl Equipment {
+    pub fn new() -> Self {
+        Equipment(BTreeMap::new())
+    }
+
+    /// Adds or replaces the gear in its slot.
+    pub fn insert(&mut self, gear: Gear) {
+        self.0.insert(gear.slot, gear);
+    }
+
+    /// Borrows the gear in a given slot, if anything is equipped there.
+    pub fn get(&self, slot: GearSlot) -> Option<&Gear> {
+        self.0.get(&slot)
+    }
+
+    /// Iterates over the equipped gear, from the main hand to the soul crystal.
+    pub fn iter(&self) -> impl Iterator<Item = &Gear> {
+        self.0.values()
+    }
+
+    /// Works out which class the gear is for. Soul crystals tell the job
+    /// directly, and otherwise the weapon tells the class or job.
+    ///
+    /// Item names and categories are worded differently on each Lodestone,
+    /// e.g. `Soul of the Paladin` or `Âme de paladin`, so the class is
+    /// found by looking for its name within them.
+    pub fn class(&self) -> Option<ClassType> {
+        let soul = self.get(GearSlot::SoulCrystal)
+            .and_then(|crystal| ClassType::find_in(&crystal.name));
+
+        //  Weapons are e.g. "Gladiator's Arm" or "Two-handed Thaumaturge's Arm"
+        let weapon = || self.get(GearSlot::MainHand)
+            .and_then(|weapon| ClassType::find_in(&weapon.category));
+
+        soul.or_else(weapon)
+    }
+
+    /// The average item level of the gear, as computed by the game.
+    ///
+    /// Every slot but the soul crystal counts, with empty slots as 0,
+    /// and weapons without an off hand count twice as they take up both.
+    pub fn average_item_level(&self) -> Option<u16> {
+        let weapon = self.get(GearSlot::MainHand)?;
+        let slots = GearSlot::ALL.iter().filter(|(_, slot)| *slot != GearSlot::SoulCrystal);
+
+        let total = slots.clone()
+            .filter_map(|(_, slot)| match (slot, self.get(*slot)) {
+                (GearSlot::OffHand, None) => Some(weapon),
+                (_, gear) => gear,
+            })
+            .map(|gear| gear.item_level as u32)
+            .sum::<u32>();
+
+        Some((total / slots.count() as u32) as u16)
+    }
+
+    /// Parses the gear shown on the main page of a character.
+    ///
+    /// Gear is best effort: slots whose tooltip can't be read are left
+    /// empty, rather than keeping the rest of the profile from being read.
+    pub(crate) fn parse(doc: &Document) -> Self {
+        let mut equipment = Equipment::new();
+
+        for (index, slot) in GearSlot::ALL {
+            let class = format!("icon-c--{}", index);
+
+            //  Empty slots have no tooltip, only a placeholder icon
+            let item = doc.find(Class(class.as_str()))
+                .find(|node| node.find(Class("db-tooltip__item__name")).next().is_some());
+
+            if let Some(gear) = item.and_then(|item| Gear::parse(slot, &item).ok()) {
+                equipment.insert(gear);
+            }
+        }
+
+        equipment
+    }
+}
+
+/// Takes the id out of an Eorzea Database link, such as
+/// `/lodestone/playguide/db/item/6c0bbb8e2b1/`.
+fn item_id(href: &str) -> Option<String> {
+    href.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|id| !id.is_empty() && href.contains("/db/item/"))
+        .map(|id| id.to_string())
+}
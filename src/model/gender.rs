@@ -1,8 +1,8 @@
-use failure::Fail;
+use thiserror::Error;
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Fail)]
-#[fail(display = "Invalid gender string '{}'", _0)]
+#[derive(Clone, Debug, Error)]
+#[error("Invalid gender string '{0}'")]
 pub struct GenderParseError(String);
 
 /// Enumeration for the gender of a character.
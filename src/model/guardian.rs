@@ -0,0 +1,76 @@
+use thiserror::Error;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Error)]
+#[error("Invalid guardian string '{0}'")]
+pub struct GuardianParseError(String);
+
+/// The twelve deities a character can choose as their guardian.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Guardian {
+    Halone,
+    Menphina,
+    Thaliak,
+    Nymeia,
+    Llymlaen,
+    Oschon,
+    Byregot,
+    Rhalgr,
+    Azeyma,
+    Naldthal,
+    Nophica,
+    Althyk,
+}
+
+/// Takes a guardian as shown on any of the Lodestones. Only the name of
+/// the deity is looked at, so both `Halone` and `Halone, the Fury` will
+/// convert to `Guardian::Halone`.
+impl FromStr for Guardian {
+    type Err = GuardianParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_uppercase();
+        let guardian = [
+            (Guardian::Halone, ["HALONE", "ハルオーネ"]),
+            (Guardian::Menphina, ["MENPHINA", "メネフィナ"]),
+            (Guardian::Thaliak, ["THALIAK", "サリャク"]),
+            (Guardian::Nymeia, ["NYMEIA", "ニメーヤ"]),
+            (Guardian::Llymlaen, ["LLYMLAEN", "リムレーン"]),
+            (Guardian::Oschon, ["OSCHON", "オシュオン"]),
+            (Guardian::Byregot, ["BYREGOT", "ビエルゴ"]),
+            (Guardian::Rhalgr, ["RHALGR", "ラールガー"]),
+            (Guardian::Azeyma, ["AZEYMA", "アーゼマ"]),
+            (Guardian::Naldthal, ["NALD'THAL", "ナルザル"]),
+            (Guardian::Nophica, ["NOPHICA", "ノフィカ"]),
+            (Guardian::Althyk, ["ALTHYK", "アルジク"]),
+        ]
+        .iter()
+        .find(|(_, names)| names.iter().any(|name| upper.starts_with(name)))
+        .map(|(guardian, _)| *guardian);
+
+        guardian.ok_or(GuardianParseError(upper))
+    }
+}
+
+/// Displays the guardian as shown on the English Lodestone.
+impl fmt::Display for Guardian {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let guardian = match *self {
+            Guardian::Halone => "Halone, the Fury",
+            Guardian::Menphina => "Menphina, the Lover",
+            Guardian::Thaliak => "Thaliak, the Scholar",
+            Guardian::Nymeia => "Nymeia, the Spinner",
+            Guardian::Llymlaen => "Llymlaen, the Navigator",
+            Guardian::Oschon => "Oschon, the Wanderer",
+            Guardian::Byregot => "Byregot, the Builder",
+            Guardian::Rhalgr => "Rhalgr, the Destroyer",
+            Guardian::Azeyma => "Azeyma, the Warden",
+            Guardian::Naldthal => "Nald'thal, the Traders",
+            Guardian::Nophica => "Nophica, the Matron",
+            Guardian::Althyk => "Althyk, the Keeper",
+        };
+
+        write!(f, "{}", guardian)
+    }
+}
@@ -0,0 +1,143 @@
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Class, Name};
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::CLIENT;
+use crate::error::LodestoneError;
+use crate::model::util::ensure_node;
+
+/// Information taken from the page the Lodestone serves while it is down.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Maintenance {
+    /// When the maintenance is announced to end, if the page says so.
+    pub ends_at: Option<SystemTime>,
+}
+
+impl Maintenance {
+    /// Checks whether a response is the maintenance page, and if so,
+    /// reads the announced end time from it.
+    ///
+    /// Only the content of the page is looked at, as the Lodestone also
+    /// answers with a 503 when it is merely overloaded.
+    pub(crate) fn detect(body: &str) -> Option<Self> {
+        //  Avoid parsing every single page, only the ones that might be it
+        if !body.contains("maintenance__body") {
+            return None;
+        }
+
+        let doc = Document::from(body);
+        let page = doc.find(Class("maintenance__body")).next()?;
+
+        Some(Maintenance {
+            ends_at: timestamps(&page.html()).last().copied(),
+        })
+    }
+}
+
+/// A maintenance announced in the Lodestone's news.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct MaintenanceNotice {
+    /// The title of the news post, without its category tag.
+    pub title: String,
+    /// The full URL of the news post.
+    pub url: String,
+    /// When the news post was published.
+    pub published: SystemTime,
+    /// When the maintenance starts, if the post has been read.
+    pub start: Option<SystemTime>,
+    /// When the maintenance ends, if the post has been read.
+    pub end: Option<SystemTime>,
+}
+
+impl MaintenanceNotice {
+    /// Gets the maintenance notices which are either ongoing or yet to come.
+    pub fn upcoming() -> Result<Vec<Self>, LodestoneError> {
+        CLIENT.upcoming_maintenance()
+    }
+
+    /// Async version of `MaintenanceNotice::upcoming`, only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn upcoming_async() -> Result<Vec<Self>, LodestoneError> {
+        CLIENT.upcoming_maintenance_async().await
+    }
+
+    /// Whether the maintenance is taking place at the given time.
+    pub fn is_ongoing(&self, now: SystemTime) -> bool {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => start <= now && now < end,
+            _ => false,
+        }
+    }
+
+    /// Whether the maintenance has yet to start at the given time.
+    pub fn is_upcoming(&self, now: SystemTime) -> bool {
+        self.start.is_some_and(|start| now < start)
+    }
+
+    /// Parses the list of notices in the maintenance category of the news.
+    ///
+    /// The start and end of each maintenance are only given in the
+    /// post itself, so they are left empty until `read_post` is used.
+    pub fn from_feed(html: &str, base_url: &str) -> Result<Vec<Self>, LodestoneError> {
+        let doc = Document::from(html);
+
+        doc.find(Class("news__list"))
+            .map(|item| Self::parse_entry(&item, base_url))
+            .collect()
+    }
+
+    /// Fills in the start and end of the maintenance from its news post.
+    ///
+    /// Times are embedded in posts as timestamps, which are rendered in
+    /// the reader's timezone, so the first two are the maintenance window.
+    pub fn read_post(&mut self, html: &str) -> Result<(), LodestoneError> {
+        let doc = Document::from(html);
+        let body = ensure_node!(doc, Class("news__detail__wrapper"));
+        let times = timestamps(&body.html());
+
+        self.start = times.first().copied();
+        self.end = times.get(1).copied();
+        Ok(())
+    }
+
+    fn parse_entry(item: &Node, base_url: &str) -> Result<Self, LodestoneError> {
+        let link = ensure_node!(item, Name("a"));
+        let href = link.attr("href")
+            .ok_or_else(|| LodestoneError::parse("news__list", "missing link"))?;
+        let title = ensure_node!(item, Class("news__list--title"));
+        let tag = title.find(Class("news__list--tag")).next().map(|tag| tag.text()).unwrap_or_default();
+        let time = ensure_node!(item, Class("news__list--time"));
+
+        Ok(MaintenanceNotice {
+            title: title.text().replacen(&tag, "", 1).trim().to_string(),
+            url: join_url(base_url, href),
+            published: timestamps(&time.html())
+                .first()
+                .copied()
+                .ok_or_else(|| LodestoneError::parse("news__list--time", "missing timestamp"))?,
+            start: None,
+            end: None,
+        })
+    }
+}
+
+/// Resolves links from the Lodestone, which are relative to the site root.
+fn join_url(base_url: &str, href: &str) -> String {
+    match href.strip_prefix("/lodestone/") {
+        Some(path) => format!("{}{}", base_url, path),
+        None => href.to_string(),
+    }
+}
+
+/// Finds the times rendered by the Lodestone's `ldst_strftime` script.
+fn timestamps(html: &str) -> Vec<SystemTime> {
+    html.split("ldst_strftime(")
+        .skip(1)
+        .filter_map(|call| {
+            let seconds = call.split(',').next()?.trim().parse::<u64>().ok()?;
+            Some(UNIX_EPOCH + Duration::from_secs(seconds))
+        })
+        .collect()
+}
@@ -1,30 +1,27 @@
-use failure::{Error, Fail, ensure};
 use select::document::Document;
-use select::predicate::{Class, Name};
+use select::node::Node;
+use select::predicate::{Class, Name, Predicate};
 
 use std::str::FromStr;
 
 use crate::model::{
-    attribute::{Attribute, Attributes},
+    attribute::{Attribute, Attributes, Resource},
+    bio::Bio,
+    city_state::CityState,
     clan::Clan,
     class::{Classes, ClassInfo, ClassType},
+    free_company::FreeCompanyRef,
+    gc::{GrandCompany, GrandCompanyRank},
+    gear::Equipment,
     gender::Gender, 
+    guardian::Guardian,
     race::Race, 
     server::Server,
     datacenter::Datacenter,
-    util::load_url
+    util::{ensure_node, HomeInfo},
 };
-
-/// Represents ways in which a search over the HTML data might go wrong.
-#[derive(Fail, Debug)]
-pub enum SearchError {
-    /// A search for a node that was required turned up empty.
-    #[fail(display = "Node not found: {}", _0)]
-    NodeNotFound(String),
-    /// A node was found, but the data inside it was malformed.
-    #[fail(display = "Invalid data found while parsing '{}'", _0)]
-    InvalidData(String),
-}
+use crate::CLIENT;
+use crate::error::LodestoneError;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 struct CharInfo {
@@ -33,24 +30,13 @@ struct CharInfo {
     gender: Gender,
 }
 
-struct HomeInfo {
-    server: Server,
-    datacenter: Datacenter,
-}
-
-/// Takes a Document and a search expression, and will return
-/// a `SearchError` if it is not found. Otherwise it will return
-/// the found node.
-macro_rules! ensure_node {
-    ($doc:ident, $search:expr) => {{
-        ensure_node!($doc, $search, 0)
-    }};
-    
-    ($doc:ident, $search:expr, $nth:expr) => {{
-        let node = $doc.find($search).nth($nth);
-        ensure!(node.is_some(), SearchError::NodeNotFound(stringify!($search).to_string() + "(" + stringify!($nth) + ")"));
-        node.unwrap()
-    }};
+/// Matches the label of a character parameter regardless of the Lodestone
+/// it came from, since the class carries a locale suffix such as `--en-us`.
+fn param_class(param: &str) -> impl Fn(&Node) -> bool {
+    let prefix = format!("character__param__text__{}--", param);
+    move |node: &Node| node
+        .attr("class")
+        .is_some_and(|class| class.split_whitespace().any(|c| c.starts_with(&prefix)))
 }
 
 /// Holds all the data for a profile retrieved via Lodestone.
@@ -59,7 +45,7 @@ pub struct Profile {
     /// The id associated with the profile
     pub user_id: u32,
     /// The profile's associated Free Company
-    pub free_company: Option<String>,
+    pub free_company: Option<FreeCompanyRef>,
     /// The profile's title
     pub title: Option<String>,
     /// The character's in-game name.
@@ -67,9 +53,14 @@ pub struct Profile {
     /// The character's nameday
     pub nameday: String,
     /// The character's guardian
-    pub guardian: String,
+    pub guardian: Guardian,
     /// The character's city state
-    pub city_state: String,
+    pub city_state: CityState,
+    /// The grand company the character is enlisted in.
+    pub grand_company: GrandCompany,
+    /// The character's rank within their grand company, or `None`
+    /// when the Lodestone words it in a way which isn't understood.
+    pub grand_company_rank: Option<GrandCompanyRank>,
     /// Which server the character is in.
     pub server: Server,
     /// Which datacenter the character is in.
@@ -82,10 +73,25 @@ pub struct Profile {
     pub gender: Gender,
     /// Max HP.
     pub hp: u32,
-    /// Max MP.
-    pub mp: u32,
+    /// Max MP, GP or CP, depending on the active class.
+    pub resource: Resource,
     /// A list of attributes and their values.
     pub attributes: Attributes,
+    /// The gear the character currently has equipped, leaving out
+    /// any piece whose tooltip couldn't be read.
+    pub gear: Equipment,
+    /// The class the character is currently on, as told by their gear.
+    pub active_class: Option<ClassType>,
+    /// The average item level of the character's gear.
+    pub average_item_level: Option<u16>,
+    /// The URL of the full body portrait of the character.
+    ///
+    /// It can be downloaded with `Lodestone::image`.
+    pub portrait_url: Option<String>,
+    /// The URL of the small picture of the character's face.
+    pub avatar_url: Option<String>,
+    /// The self-introduction written by the player, if any.
+    pub bio: Option<Bio>,
     /// A list of classes and their corresponding levels.
     classes: Classes,
 }
@@ -95,35 +101,86 @@ impl Profile {
     /// 
     /// If you don't have the id, it is possible to use a 
     /// `SearchBuilder` in order to find their profile directly.
-    pub fn get(user_id: u32) -> Result<Self, Error> {
-        let main_doc = load_url(user_id, None)?;
-        let classes_doc = load_url(user_id, Some("class_job"))?;
+    pub fn get(user_id: u32) -> Result<Self, LodestoneError> {
+        CLIENT.profile(user_id)
+    }
+
+    /// Async version of `Profile::get`, only available with the `async` feature.
+    ///
+    /// Fetching is done without blocking, while parsing is shared with
+    /// the blocking API so both always produce the same results.
+    #[cfg(feature = "async")]
+    pub async fn get_async(user_id: u32) -> Result<Self, LodestoneError> {
+        CLIENT.profile_async(user_id).await
+    }
+
+    /// Gets the profiles of many characters at once, returning
+    /// the result for each id in the order they were given.
+    ///
+    /// This is much faster than calling `Profile::get` for each id,
+    /// for instance to get the profile of every result of a search.
+    pub fn get_many<I: IntoIterator<Item = u32>>(user_ids: I) -> Vec<(u32, Result<Self, LodestoneError>)> {
+        CLIENT.profiles(user_ids)
+    }
+
+    /// Async version of `Profile::get_many`, only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn get_many_async<I: IntoIterator<Item = u32>>(user_ids: I) -> Vec<(u32, Result<Self, LodestoneError>)> {
+        CLIENT.profiles_async(user_ids).await
+    }
+
+    /// Parses a profile out of already downloaded HTML, given the main
+    /// character page and its `class_job` subpage.
+    ///
+    /// This is useful for re-parsing stored pages without fetching them again.
+    pub fn from_html(user_id: u32, main_html: &str, classes_html: &str) -> Result<Self, LodestoneError> {
+        Self::from_documents(user_id, &Document::from(main_html), &Document::from(classes_html))
+    }
+
+    /// Same as `Profile::from_html`, but for already parsed documents.
+    ///
+    /// Fails with `LodestoneError::PrivateProfile` if the pages are those
+    /// of a character whose owner has hidden their profile.
+    pub fn from_documents(user_id: u32, main_doc: &Document, classes_doc: &Document) -> Result<Self, LodestoneError> {
+        if Self::is_private(main_doc) || Self::is_private(classes_doc) {
+            return Err(LodestoneError::PrivateProfile(user_id));
+        }
 
         //  Holds the string for Race, Clan, and Gender in that order
-        let char_info = Self::parse_char_info(&main_doc)?;
+        let char_info = Self::parse_char_info(main_doc)?;
 
         //  Holds the string for Server, Datacenter in that order
-        let home_info = Self::parse_home_info(&main_doc)?;
+        let home_info = Self::parse_home_info(main_doc)?;
 
-        let (hp, mp) = Self::parse_char_param(&main_doc)?;
+        let (hp, resource) = Self::parse_char_param(main_doc)?;
+        let (grand_company, grand_company_rank) = Self::parse_grand_company(main_doc);
+        let gear = Equipment::parse(main_doc);
 
         Ok(Self {
             user_id,
-            free_company: Self::parse_free_company(&main_doc),
-            title: Self::parse_title(&main_doc),
-            name: Self::parse_name(&main_doc)?,
-            nameday: Self::parse_nameday(&main_doc)?,
-            guardian: Self::parse_guardian(&main_doc)?,
-            city_state: Self::parse_city_state(&main_doc)?,
+            free_company: FreeCompanyRef::parse(main_doc),
+            title: Self::parse_title(main_doc),
+            name: Self::parse_name(main_doc)?,
+            nameday: Self::parse_nameday(main_doc)?,
+            guardian: Self::parse_guardian(main_doc)?,
+            city_state: Self::parse_city_state(main_doc)?,
+            grand_company,
+            grand_company_rank,
             server: home_info.server,
             datacenter: home_info.datacenter,
             race: char_info.race,
             clan: char_info.clan,
             gender: char_info.gender,
             hp,
-            mp,
-            attributes: Self::parse_attributes(&main_doc)?,
-            classes: Self::parse_classes(&classes_doc)?,
+            resource,
+            attributes: Self::parse_attributes(main_doc)?,
+            active_class: gear.class(),
+            average_item_level: Self::parse_average_item_level(main_doc).or_else(|| gear.average_item_level()),
+            gear,
+            portrait_url: Self::parse_portrait_url(main_doc),
+            avatar_url: Self::parse_avatar_url(main_doc),
+            bio: Self::parse_bio(main_doc),
+            classes: Self::parse_classes(classes_doc)?,
         })
     }
 
@@ -134,10 +191,7 @@ impl Profile {
     /// return None. If Paladin is unlocked, both Gladiator and
     /// Paladin will return the same level.
     pub fn level(&self, class: ClassType) -> Option<u32> {
-        match self.class_info(class) {
-            Some(v) => Some(v.level),
-            None => None
-        }
+        self.class_info(class).map(|v| v.level)
     }
 
     /// Gets this profile's data for a given class
@@ -150,72 +204,73 @@ impl Profile {
         &self.classes
     }
 
-    fn parse_free_company(doc: &Document) -> Option<String> {
-        match doc.find(Class("character__freecompany__name")).next() {
-            Some(node) => Some(
-                node.text().strip_prefix("Free Company").unwrap_or(&node.text()).to_string()
-            ),
-            None => None,
-        }
+    /// Private profiles still show the character's name and world in the
+    /// header, but the rest of the page is replaced by a notice.
+    fn is_private(doc: &Document) -> bool {
+        let has = |class| doc.find(Class(class)).next().is_some();
+
+        has("frame__chara__name")
+            && has("parts__zero")
+            && !has("character__param")
+            && !has("character__job__level")
     }
 
     fn parse_title(doc: &Document) -> Option<String> {
-        match doc.find(Class("frame__chara__title")).next() {
-            Some(node) => Some(node.text()),
-            None => None,
-        }
+        doc.find(Class("frame__chara__title")).next().map(|node| node.text())
     }
 
-    fn parse_name(doc: &Document) -> Result<String, Error> {
+    fn parse_name(doc: &Document) -> Result<String, LodestoneError> {
         Ok(ensure_node!(doc, Class("frame__chara__name")).text())
     }
 
-    fn parse_nameday(doc: &Document) -> Result<String, Error> {
+    fn parse_nameday(doc: &Document) -> Result<String, LodestoneError> {
         Ok(ensure_node!(doc, Class("character-block__birth")).text())
     }
 
-    fn parse_guardian(doc: &Document) -> Result<String, Error> {
-        Ok(ensure_node!(doc, Class("character-block__name"), 1).text())
+    fn parse_guardian(doc: &Document) -> Result<Guardian, LodestoneError> {
+        Ok(Guardian::from_str(&ensure_node!(doc, Class("character-block__name"), 1).text())?)
     }
 
-    fn parse_city_state(doc: &Document) -> Result<String, Error> {
-        Ok(ensure_node!(doc, Class("character-block__name"), 2).text())
+    fn parse_city_state(doc: &Document) -> Result<CityState, LodestoneError> {
+        Ok(CityState::from_str(&ensure_node!(doc, Class("character-block__name"), 2).text())?)
     }
 
-    fn parse_home_info(doc: &Document) -> Result<HomeInfo, Error> {
-        let text = ensure_node!(doc, Class("frame__chara__world")).text();
-        let mut server = text.split("\u{A0}").next();
+    /// The grand company block is only there for enlisted characters,
+    /// and reads e.g. `Maelstrom / Second Storm Lieutenant`. Blocks which
+    /// don't name a known company are treated as no company at all.
+    fn parse_grand_company(doc: &Document) -> (GrandCompany, Option<GrandCompanyRank>) {
+        let block = match doc.find(Class("character-block__name")).nth(3) {
+            Some(block) => block.text(),
+            None => return (GrandCompany::Unaffiliated, None),
+        };
 
-        ensure!(server.is_some(), SearchError::InvalidData("Could not find server/datacenter string.".into()));
+        let (company, rank) = block.split_once('/').unwrap_or((&block, ""));
+        match GrandCompany::from_str(company) {
+            Ok(company) => (company, GrandCompanyRank::from_name(company, rank).ok()),
+            Err(_) => (GrandCompany::Unaffiliated, None),
+        }
+    }
 
-        // String comes in format Server [Datacenter]
-        let home_info = server
-            .unwrap()
-            .split_whitespace()
-            .map(|e| e.replace(&['[', ']'], ""))
-            .collect::<Vec<String>>();
-            
-        Ok(HomeInfo {
-            server: Server::from_str(&home_info[0])?,
-            datacenter: Datacenter::from_str(&home_info[1])?,
-        })
+    fn parse_home_info(doc: &Document) -> Result<HomeInfo, LodestoneError> {
+        HomeInfo::parse(&ensure_node!(doc, Class("frame__chara__world")).text())
     }
 
-    fn parse_char_info(doc: &Document) -> Result<CharInfo, Error> {
+    fn parse_char_info(doc: &Document) -> Result<CharInfo, LodestoneError> {
         let char_block = {
             let mut block = ensure_node!(doc, Class("character-block__name")).inner_html();
-            block = block.replace(" ", "_");
+            block = block.replace(' ', "_");
             block = block.replace("<br>", " ");
             block.replace("_/_", " ")
         };
 
         let char_info = char_block
             .split_whitespace()
-            .map(|e| e.replace("_", " "))
-            .map(|e| e.into())
+            .map(|e| e.replace('_', " "))
             .collect::<Vec<String>>();
 
-        ensure!(char_info.len() == 3 || char_info.len() == 4, SearchError::InvalidData("character block name".into()));
+        if char_info.len() != 3 && char_info.len() != 4 {
+            return Err(LodestoneError::parse("character-block__name", "expected race, clan and gender"));
+        }
 
         //  If the length is 4, then the race is "Au Ra"
         if char_info.len() == 4 {
@@ -233,28 +288,66 @@ impl Profile {
         }
     }
 
-    fn parse_char_param(doc: &Document) -> Result<(u32, u32), Error> {
+    fn parse_char_param(doc: &Document) -> Result<(u32, Resource), LodestoneError> {
         let attr_block = ensure_node!(doc, Class("character__param"));
         let mut hp = None;
-        let mut mp = None;
+        let mut resource = None;
         for item in attr_block.find(Name("li")) {
-            if item.find(Class("character__param__text__hp--en-us")).count() == 1 {
-                hp = Some(ensure_node!(item, Name("span")).text().parse::<u32>()?);
-            } else if item.find(Class("character__param__text__mp--en-us")).count() == 1 ||
-                      item.find(Class("character__param__text__gp--en-us")).count() == 1 ||
-                      item.find(Class("character__param__text__cp--en-us")).count() == 1 {
-                // doh/dol jobs change the css now to show GP/CP. if any is present, store as mp
-                mp = Some(ensure_node!(item, Name("span")).text().parse::<u32>()?);
+            //  DoH/DoL jobs show GP/CP in place of MP
+            let kind: Option<fn(u32) -> Resource> = if item.find(param_class("mp")).count() == 1 {
+                Some(Resource::Mp)
+            } else if item.find(param_class("gp")).count() == 1 {
+                Some(Resource::Gp)
+            } else if item.find(param_class("cp")).count() == 1 {
+                Some(Resource::Cp)
             } else {
-                continue
+                None
+            };
+
+            if item.find(param_class("hp")).count() == 1 {
+                hp = Some(ensure_node!(item, Name("span")).text().parse::<u32>()?);
+            } else if let Some(kind) = kind {
+                resource = Some(kind(ensure_node!(item, Name("span")).text().parse::<u32>()?));
             }
         }
-        ensure!(hp.is_some() && mp.is_some(), SearchError::InvalidData("character__param".into()));
+        match (hp, resource) {
+            (Some(hp), Some(resource)) => Ok((hp, resource)),
+            _ => Err(LodestoneError::parse("character__param", "missing HP or MP")),
+        }
+    }
+
+    /// The average item level is shown as e.g. "Average Item Level 361".
+    fn parse_average_item_level(doc: &Document) -> Option<u16> {
+        doc.find(Class("character__detail__avg"))
+            .next()?
+            .text()
+            .split_whitespace()
+            .find_map(|word| word.parse().ok())
+    }
+
+    /// The portrait is shown scaled down, but links to the full size image.
+    fn parse_portrait_url(doc: &Document) -> Option<String> {
+        let image = doc.find(Class("character__detail__image")).next()?;
+
+        image.find(Name("a"))
+            .next()
+            .and_then(|link| link.attr("href"))
+            .or_else(|| image.find(Name("img")).next()?.attr("src"))
+            .map(|url| url.to_string())
+    }
+
+    fn parse_avatar_url(doc: &Document) -> Option<String> {
+        doc.find(Class("frame__chara__face").descendant(Name("img")))
+            .next()?
+            .attr("src")
+            .map(|url| url.to_string())
+    }
 
-        Ok((hp.unwrap(), mp.unwrap()))
+    fn parse_bio(doc: &Document) -> Option<Bio> {
+        Bio::parse(&doc.find(Class("character__selfintroduction")).next()?)
     }
 
-    fn parse_attributes(doc: &Document) -> Result<Attributes, Error> {
+    fn parse_attributes(doc: &Document) -> Result<Attributes, LodestoneError> {
         let block = ensure_node!(doc, Class("character__profile__data"));
         let mut attributes = Attributes::new();
         for item in block.find(Name("tr")) {
@@ -267,7 +360,7 @@ impl Profile {
         Ok(attributes)
     }
 
-    fn parse_classes(doc: &Document) -> Result<Classes, Error> {
+    fn parse_classes(doc: &Document) -> Result<Classes, LodestoneError> {
         let mut classes = Classes::new();
 
         for list in doc.find(Class("character__content")).take(4) {
@@ -277,29 +370,26 @@ impl Profile {
                     "-" => None,
                     level => {
                         let text = ensure_node!(item, Class("character__job__exp")).text();
-                        let mut parts = text.split(" / ");
-                        let current_xp = parts.next();
-                        ensure!(current_xp.is_some(), SearchError::InvalidData("character__job__exp".into()));
-                        let max_xp = parts.next();
-                        ensure!(max_xp.is_some(), SearchError::InvalidData("character__job__exp".into()));
+                        let (current_xp, max_xp) = text
+                            .split_once(" / ")
+                            .ok_or_else(|| LodestoneError::parse("character__job__exp", "expected current and max XP"))?;
                         Some(ClassInfo{
                             level: level.parse()?,
-                            current_xp: match current_xp.unwrap() {
+                            current_xp: match current_xp {
                                 "--" => None,
-                                value => Some(value.replace(",", "").parse()?)
+                                value => Some(value.replace(',', "").parse()?)
                             },
-                            max_xp: match max_xp.unwrap() {
+                            max_xp: match max_xp {
                                 "--" => None,
-                                value => Some(value.replace(",", "").parse()?)
+                                value => Some(value.replace(',', "").parse()?)
                             },
                         })
                     }
                 };
 
                 //  For classes that have multiple titles (e.g., Paladin / Gladiator), grab the first one.
-                let name = name.split(" / ").next();
-                ensure!(name.is_some(), SearchError::InvalidData("character__job__name".into()));
-                let class = ClassType::from_str(&name.unwrap())?;
+                let name = name.split(" / ").next().unwrap_or_default();
+                let class = ClassType::from_str(name)?;
 
                 //  If the class added was a secondary job, then associated that level
                 //  with its lower level counterpart as well. This makes returning the
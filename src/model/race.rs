@@ -1,8 +1,8 @@
-use failure::Fail;
+use thiserror::Error;
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Fail)]
-#[fail(display = "Invalid race string '{}'", _0)]
+#[derive(Clone, Debug, Error)]
+#[error("Invalid race string '{0}'")]
 pub struct RaceParseError(String);
 
 /// Models the races available in XIV.
@@ -18,19 +18,37 @@ pub enum Race {
     Viera,
 }
 
+impl Race {
+    /// The id used for this race by the game and in Lodestone URLs.
+    pub fn id(self) -> u32 {
+        match self {
+            Race::Hyur => 1,
+            Race::Elezen => 2,
+            Race::Lalafell => 3,
+            Race::Miqote => 4,
+            Race::Roegadyn => 5,
+            Race::Aura => 6,
+            Race::Hrothgar => 7,
+            Race::Viera => 8,
+        }
+    }
+}
+
+/// Case insensitive FromStr impl for races, accepting the names
+/// used on the English, German, French and Japanese Lodestones.
 impl FromStr for Race {
     type Err = RaceParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match &*s.to_uppercase() {
-            "AU RA" => Ok(Race::Aura),
-            "ELEZEN" => Ok(Race::Elezen),
-            "HYUR" => Ok(Race::Hyur),
-            "LALAFELL" => Ok(Race::Lalafell),
-            "MIQO'TE" => Ok(Race::Miqote),
-            "ROEGADYN" => Ok(Race::Roegadyn),
-            "HROTHGAR" => Ok(Race::Hrothgar),
-            "VIERA" => Ok(Race::Viera),
+            "AU RA" | "AO RA" | "アウラ" => Ok(Race::Aura),
+            "ELEZEN" | "エレゼン" => Ok(Race::Elezen),
+            "HYUR" | "HYURAN" | "ヒューラン" => Ok(Race::Hyur),
+            "LALAFELL" | "ララフェル" => Ok(Race::Lalafell),
+            "MIQO'TE" | "ミコッテ" => Ok(Race::Miqote),
+            "ROEGADYN" | "ルガディン" => Ok(Race::Roegadyn),
+            "HROTHGAR" | "ロスガル" => Ok(Race::Hrothgar),
+            "VIERA" | "VIÉRA" | "ヴィエラ" => Ok(Race::Viera),
             x => Err(RaceParseError(x.into())),
         }
     }
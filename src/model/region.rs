@@ -0,0 +1,58 @@
+use thiserror::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::model::datacenter::Datacenter;
+
+#[derive(Clone, Debug, Error)]
+#[error("Invalid region string '{0}'")]
+pub struct RegionParseError(String);
+
+/// The regions datacenters are grouped into.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Region {
+    NA,
+    EU,
+    OCE,
+    JP,
+}
+
+impl Region {
+    /// The datacenters located in this region.
+    pub fn datacenters(self) -> &'static [Datacenter] {
+        match self {
+            Region::NA => &[Datacenter::Aether, Datacenter::Primal, Datacenter::Crystal, Datacenter::Dynamis],
+            Region::EU => &[Datacenter::Chaos, Datacenter::Light, Datacenter::Shadow],
+            Region::OCE => &[Datacenter::Materia],
+            Region::JP => &[Datacenter::Elemental, Datacenter::Gaia, Datacenter::Mana, Datacenter::Meteor],
+        }
+    }
+}
+
+/// Case insensitive FromStr impl for regions.
+impl FromStr for Region {
+    type Err = RegionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.to_uppercase() {
+            "NA" | "NORTH AMERICA" => Ok(Region::NA),
+            "EU" | "EUROPE" => Ok(Region::EU),
+            "OCE" | "OCEANIA" => Ok(Region::OCE),
+            "JP" | "JAPAN" => Ok(Region::JP),
+            x => Err(RegionParseError(x.into())),
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let region = match *self {
+            Region::NA => "NA",
+            Region::EU => "EU",
+            Region::OCE => "OCE",
+            Region::JP => "JP",
+        };
+
+        write!(f, "{}", region)
+    }
+}
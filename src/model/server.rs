@@ -1,9 +1,11 @@
-use failure::Fail;
+use thiserror::Error;
 use std::fmt;
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Fail)]
-#[fail(display = "Invalid server string '{}'", _0)]
+use crate::model::datacenter::Datacenter;
+
+#[derive(Clone, Debug, Error)]
+#[error("Invalid server string '{0}'")]
 pub struct ServerParseError(String);
 
 /// An enumeration for the servers that are currently available.
@@ -129,13 +131,55 @@ pub enum Server {
     Zeromus,
 }
 
+impl Server {
+    /// The datacenter this server is part of.
+    pub fn datacenter(self) -> Datacenter {
+        match self {
+            Server::Adamantoise | Server::Cactuar | Server::Faerie | Server::Gilgamesh |
+            Server::Jenova | Server::Midgardsormr | Server::Sargatanas | Server::Siren => Datacenter::Aether,
+
+            Server::Behemoth | Server::Excalibur | Server::Exodus | Server::Famfrit |
+            Server::Hyperion | Server::Lamia | Server::Leviathan | Server::Ultros => Datacenter::Primal,
+
+            Server::Balmung | Server::Brynhildr | Server::Coeurl | Server::Diabolos |
+            Server::Goblin | Server::Malboro | Server::Mateus | Server::Zalera => Datacenter::Crystal,
+
+            Server::Halicarnassus | Server::Maduin | Server::Marilith | Server::Seraph |
+            Server::Cuchulainn | Server::Kraken | Server::Rafflesia | Server::Golem => Datacenter::Dynamis,
+
+            Server::Cerberus | Server::Louisoix | Server::Moogle | Server::Omega |
+            Server::Phantom | Server::Ragnarok | Server::Sagittarius | Server::Spriggan => Datacenter::Chaos,
+
+            Server::Alpha | Server::Lich | Server::Odin | Server::Phoenix |
+            Server::Raiden | Server::Shiva | Server::Twintania | Server::Zodiark => Datacenter::Light,
+
+            Server::Innocence | Server::Pixie | Server::Titania | Server::Tycoon => Datacenter::Shadow,
+
+            Server::Bismarck | Server::Ravana | Server::Sephirot | Server::Sophia |
+            Server::Zurvan => Datacenter::Materia,
+
+            Server::Aegis | Server::Atomos | Server::Carbuncle | Server::Garuda |
+            Server::Gungnir | Server::Kujata | Server::Tonberry | Server::Typhon => Datacenter::Elemental,
+
+            Server::Alexander | Server::Bahamut | Server::Durandal | Server::Fenrir |
+            Server::Ifrit | Server::Ridill | Server::Tiamat | Server::Ultima => Datacenter::Gaia,
+
+            Server::Anima | Server::Asura | Server::Chocobo | Server::Hades |
+            Server::Ixion | Server::Masamune | Server::Pandaemonium | Server::Titan => Datacenter::Mana,
+
+            Server::Belias | Server::Mandragora | Server::Ramuh | Server::Shinryu |
+            Server::Unicorn | Server::Valefor | Server::Yojimbo | Server::Zeromus => Datacenter::Meteor,
+        }
+    }
+}
+
 /// Case insensitive FromStr impl for servers.
 impl FromStr for Server {
     type Err = ServerParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match &*s.to_uppercase() {
-            /// NA
+            //  NA
             //  Aether
             "ADAMANTOISE" => Ok(Server::Adamantoise),
             "CACTUAR" => Ok(Server::Cactuar),
@@ -176,7 +220,7 @@ impl FromStr for Server {
             "RAFFLESIA" => Ok(Server::Rafflesia),
             "GOLEM" => Ok(Server::Golem),
 
-            /// EU
+            //  EU
             //  Chaos
             "CERBERUS" => Ok(Server::Cerberus),
             "LOUISOIX" => Ok(Server::Louisoix),
@@ -203,7 +247,7 @@ impl FromStr for Server {
             "TITANIA" => Ok(Server::Titania),
             "TYCOON" => Ok(Server::Tycoon),
 
-            /// OCE
+            //  OCE
             //  Materia
             "BISMARCK" => Ok(Server::Bismarck),
             "RAVANA" => Ok(Server::Ravana),
@@ -211,7 +255,7 @@ impl FromStr for Server {
             "SOPHIA" => Ok(Server::Sophia),
             "ZURVAN" => Ok(Server::Zurvan),
 
-            /// JP
+            //  JP
             //  Elemental
             "AEGIS" => Ok(Server::Aegis),
             "ATOMOS" => Ok(Server::Atomos),
@@ -260,7 +304,7 @@ impl FromStr for Server {
 impl fmt::Display for Server {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let server = match *self {
-            /// NA
+            //  NA
             //  Aether
             Server::Adamantoise => "Adamantoise",
             Server::Cactuar =>     "Cactuar",
@@ -301,7 +345,7 @@ impl fmt::Display for Server {
             Server::Rafflesia =>     "Rafflesia",
             Server::Golem =>         "Golem",
                                     
-            /// EU                  
+            //  EU                  
             //  Chaos               
             Server::Cerberus =>    "Cerberus",
             Server::Louisoix =>    "Louisoix",
@@ -328,7 +372,7 @@ impl fmt::Display for Server {
             Server::Titania =>     "Titania",
             Server::Tycoon =>      "Tycoon",
                                     
-            /// OCE                 
+            //  OCE                 
             //  Materia             
             Server::Bismarck =>    "Bismarck",
             Server::Ravana =>      "Ravana",
@@ -336,7 +380,7 @@ impl fmt::Display for Server {
             Server::Sophia =>      "Sophia",
             Server::Zurvan =>      "Zurvan",
                                     
-            /// JP                  
+            //  JP                  
             //  Elemental           
             Server::Aegis =>       "Aegis",
             Server::Atomos =>      "Atomos",
@@ -1,18 +1,64 @@
-use failure::Error;
-use select::document::Document;
+use std::str::FromStr;
 
-use crate::CLIENT;
+use crate::error::LodestoneError;
+use crate::model::datacenter::Datacenter;
+use crate::model::domain::Domain;
+use crate::model::server::Server;
 
-/// The URL base for profiles.
-static BASE_PROFILE_URL: &str = "https://na.finalfantasyxiv.com/lodestone/character/";
+/// Takes a Document and a search expression, and will return
+/// a `LodestoneError::Parse` if it is not found. Otherwise it
+/// will return the found node.
+macro_rules! ensure_node {
+    ($doc:ident, $search:expr) => {{
+        ensure_node!($doc, $search, 0)
+    }};
+    
+    ($doc:ident, $search:expr, $nth:expr) => {{
+        match $doc.find($search).nth($nth) {
+            Some(node) => node,
+            None => return Err($crate::error::LodestoneError::parse(
+                stringify!($search).to_string() + "(" + stringify!($nth) + ")",
+                "node not found",
+            ).into()),
+        }
+    }};
+}
+
+pub(crate) use ensure_node;
+
+/// The world a character lives on.
+pub(crate) struct HomeInfo {
+    pub server: Server,
+    pub datacenter: Datacenter,
+}
+
+impl HomeInfo {
+    /// Parses a world string, which comes in the format `Server [Datacenter]`.
+    pub fn parse(text: &str) -> Result<Self, LodestoneError> {
+        let home_info = text
+            .split('\u{A0}')
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(|e| e.replace(['[', ']'], ""))
+            .collect::<Vec<String>>();
 
-pub(crate) fn load_url(user_id: u32, subpage: Option<&str>) -> Result<Document, Error> {
+        if home_info.len() != 2 {
+            return Err(LodestoneError::parse("server/datacenter", format!("invalid string '{}'", text)));
+        }
+
+        Ok(HomeInfo {
+            server: Server::from_str(&home_info[0])?,
+            datacenter: Datacenter::from_str(&home_info[1])?,
+        })
+    }
+}
+
+/// Creates the URL of a profile page, or one of its subpages.
+pub(crate) fn profile_url(domain: Domain, user_id: u32, subpage: Option<&str>) -> String {
     let subpage = match subpage {
         None => "".to_string(),
         Some(v) => format!("{}/", v)
     };
-    let mut response = CLIENT.get(&format!("{}{}/{}", BASE_PROFILE_URL, user_id, subpage)).send()?;
-    let text = response.text()?;
-    Ok(Document::from(text.as_str()))
+    format!("{}character/{}/{}", domain.base_url(), user_id, subpage)
 }
-
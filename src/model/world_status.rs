@@ -0,0 +1,138 @@
+use thiserror::Error;
+use select::document::Document;
+use select::node::Node;
+use select::predicate::{Class, Name, Predicate};
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::CLIENT;
+use crate::error::LodestoneError;
+use crate::model::server::Server;
+use crate::model::util::ensure_node;
+
+#[derive(Clone, Debug, Error)]
+#[error("Invalid congestion string '{0}'")]
+pub struct CongestionParseError(String);
+
+/// Whether a server can currently be played on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ServerState {
+    Online,
+    PartialMaintenance,
+    Maintenance,
+}
+
+/// How populated a server is, which decides whether new characters
+/// can be made there and whether transfers to it get bonuses.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Congestion {
+    Standard,
+    Preferred,
+    Congested,
+    New,
+}
+
+/// Case insensitive FromStr impl for congestion categories, accepting
+/// the names used on the English, German, French and Japanese Lodestones.
+impl FromStr for Congestion {
+    type Err = CongestionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.trim().to_uppercase() {
+            "STANDARD" | "標準" => Ok(Congestion::Standard),
+            "PREFERRED" | "BEVORZUGT" | "PRIVILÉGIÉ" | "優遇" => Ok(Congestion::Preferred),
+            "CONGESTED" | "ÜBERLASTET" | "ENCOMBRÉ" | "混雑" => Ok(Congestion::Congested),
+            "NEW" | "NEU" | "NOUVEAU" | "新規" => Ok(Congestion::New),
+            x => Err(CongestionParseError(x.into())),
+        }
+    }
+}
+
+/// The status of a single server.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ServerStatus {
+    pub server: Server,
+    pub state: ServerState,
+    pub congestion: Congestion,
+    /// Whether new characters can be created on the server.
+    pub character_creation: bool,
+}
+
+/// Holds the status of every server, as shown on the world status page.
+#[derive(Debug, Default)]
+pub struct WorldStatus {
+    servers: HashMap<Server, ServerStatus>,
+    errors: Vec<LodestoneError>,
+}
+
+impl WorldStatus {
+    /// Gets the current status of every server.
+    pub fn get() -> Result<Self, LodestoneError> {
+        CLIENT.world_status()
+    }
+
+    /// Async version of `WorldStatus::get`, only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn get_async() -> Result<Self, LodestoneError> {
+        CLIENT.world_status_async().await
+    }
+
+    /// Parses an already downloaded world status page.
+    ///
+    /// Servers are parsed one by one, so that one which can't be parsed,
+    /// such as a newly opened world, doesn't hide the status of the others.
+    pub fn from_html(html: &str) -> Result<Self, LodestoneError> {
+        let doc = Document::from(html);
+        let mut status = WorldStatus::default();
+
+        for item in doc.find(Class("world-list__item")) {
+            match Self::parse_server(&item) {
+                Ok(server) => {
+                    status.servers.insert(server.server, server);
+                },
+                Err(e) => status.errors.push(e),
+            }
+        }
+
+        Ok(status)
+    }
+
+    /// Gets the status of a server, if it was listed.
+    pub fn status(&self, server: Server) -> Option<&ServerStatus> {
+        self.servers.get(&server)
+    }
+
+    /// Iterates over the status of every listed server.
+    pub fn iter(&self) -> impl Iterator<Item = &ServerStatus> {
+        self.servers.values()
+    }
+
+    /// Why the servers which are missing from the status couldn't be parsed.
+    pub fn errors(&self) -> &[LodestoneError] {
+        &self.errors
+    }
+
+    fn parse_server(item: &Node) -> Result<ServerStatus, LodestoneError> {
+        let name = ensure_node!(item, Class("world-list__world_name").descendant(Name("p"))).text();
+        let category = ensure_node!(item, Class("world-list__world_category").descendant(Name("p"))).text();
+        let status_icon = ensure_node!(item, Class("world-list__status_icon").descendant(Name("i")));
+        let creation_icon = ensure_node!(item, Class("world-list__create_character").descendant(Name("i")));
+
+        //  The state is only told by the icon, as the tooltip text is localized
+        let state = if status_icon.is(Class("world-ic__1")) {
+            ServerState::Online
+        } else if status_icon.is(Class("world-ic__2")) {
+            ServerState::PartialMaintenance
+        } else {
+            ServerState::Maintenance
+        };
+
+        Ok(ServerStatus {
+            server: Server::from_str(name.trim())?,
+            state,
+            congestion: Congestion::from_str(&category)?,
+            character_creation: creation_icon.is(Class("world-ic__available")),
+        })
+    }
+}
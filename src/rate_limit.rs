@@ -0,0 +1,97 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How many requests a `Lodestone` client may make, shared by every
+/// request made through the client and its clones.
+///
+/// Requests beyond the limit are not refused, but delayed until a
+/// token is available, following the token bucket algorithm.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    requests_per_second: Option<f64>,
+    burst: u32,
+}
+
+impl Default for RateLimit {
+    /// One request per second, with bursts of up to 5 requests. The Lodestone
+    /// tolerates this for long syncs without temporarily blocking the client.
+    fn default() -> Self {
+        RateLimit::new(1.0, 5)
+    }
+}
+
+impl RateLimit {
+    /// Allows `requests_per_second` requests on average, and up to
+    /// `burst` requests at once after the client has been idle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` is not a positive number.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(requests_per_second > 0.0, "requests_per_second must be positive");
+
+        RateLimit {
+            requests_per_second: Some(requests_per_second),
+            burst: burst.max(1),
+        }
+    }
+
+    /// Disables rate limiting entirely.
+    pub fn unlimited() -> Self {
+        RateLimit {
+            requests_per_second: None,
+            burst: 1,
+        }
+    }
+}
+
+/// The token bucket enforcing a `RateLimit`.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Tokens left, which goes negative when requests are waiting.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        RateLimiter {
+            limit,
+            bucket: Mutex::new(Bucket {
+                tokens: limit.burst as f64,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token, returning how long to wait before making the request.
+    pub fn reserve(&self) -> Duration {
+        self.reserve_at(Instant::now())
+    }
+
+    pub fn reserve_at(&self, now: Instant) -> Duration {
+        let rate = match self.limit.requests_per_second {
+            Some(rate) => rate,
+            None => return Duration::ZERO,
+        };
+
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(self.limit.burst as f64);
+        bucket.updated = bucket.updated.max(now);
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+}
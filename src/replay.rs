@@ -0,0 +1,138 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "async")]
+use crate::client::{AsyncTransport, BoxFuture};
+use crate::cache::{file_name, split_first_line};
+use crate::client::{Response, Transport};
+use crate::error::LodestoneError;
+
+/// A `Transport` which saves every response it gets to a fixtures
+/// directory, so they can later be served by a `ReplayTransport`.
+///
+/// Each response is stored in its own file named after the URL, holding
+/// the status code on the first line followed by the raw body.
+#[derive(Clone, Debug)]
+pub struct RecordingTransport<T> {
+    dir: PathBuf,
+    inner: T,
+}
+
+impl<T> RecordingTransport<T> {
+    /// Records the responses of `inner` into `dir`.
+    pub fn new<P: AsRef<Path>>(dir: P, inner: T) -> Self {
+        RecordingTransport {
+            dir: dir.as_ref().to_path_buf(),
+            inner,
+        }
+    }
+
+    fn save(&self, url: &str, status: u16, body: &[u8]) -> Result<(), LodestoneError> {
+        fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(fixture_path(&self.dir, url), [format!("{}\n", status).as_bytes(), body].concat()))
+            .map_err(LodestoneError::Io)
+    }
+}
+
+impl<T: Transport> Transport for RecordingTransport<T> {
+    fn get(&self, url: &str) -> Result<Response, LodestoneError> {
+        let response = self.inner.get(url)?;
+        self.save(url, response.status, response.body.as_bytes())?;
+        Ok(response)
+    }
+
+    fn get_bytes(&self, url: &str) -> Result<Response<Vec<u8>>, LodestoneError> {
+        let response = self.inner.get_bytes(url)?;
+        self.save(url, response.status, &response.body)?;
+        Ok(response)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: AsyncTransport> AsyncTransport for RecordingTransport<T> {
+    fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response, LodestoneError>> {
+        Box::pin(async move {
+            let response = self.inner.get(url).await?;
+            self.save(url, response.status, response.body.as_bytes())?;
+            Ok(response)
+        })
+    }
+
+    fn get_bytes<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response<Vec<u8>>, LodestoneError>> {
+        Box::pin(async move {
+            let response = self.inner.get_bytes(url).await?;
+            self.save(url, response.status, &response.body)?;
+            Ok(response)
+        })
+    }
+}
+
+/// A `Transport` which never touches the network, and instead serves
+/// the responses saved by a `RecordingTransport`.
+///
+/// Requests for URLs which were never recorded fail with a
+/// `LodestoneError::Io` error naming the missing file, which is never retried.
+#[derive(Clone, Debug)]
+pub struct ReplayTransport {
+    dir: PathBuf,
+}
+
+impl ReplayTransport {
+    /// Serves the responses recorded in `dir`.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        ReplayTransport {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn load(&self, url: &str) -> Result<Response<Vec<u8>>, LodestoneError> {
+        let path = fixture_path(&self.dir, url);
+        let contents = fs::read(&path).map_err(|e| {
+            LodestoneError::Io(io::Error::new(e.kind(), format!("no fixture at {}", path.display())))
+        })?;
+
+        let invalid = || LodestoneError::parse(path.display().to_string(), "invalid status line");
+        let (status, body) = split_first_line(&contents).ok_or_else(invalid)?;
+        let status = status.trim().parse().map_err(|_| invalid())?;
+
+        Ok(Response {
+            status,
+            body: body.to_vec(),
+            retry_after: None,
+        })
+    }
+
+    fn load_text(&self, url: &str) -> Result<Response, LodestoneError> {
+        let response = self.load(url)?;
+        let body = String::from_utf8(response.body)
+            .map_err(|_| LodestoneError::parse(url, "fixture is not valid UTF-8"))?;
+
+        Ok(Response::new(response.status, body))
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn get(&self, url: &str) -> Result<Response, LodestoneError> {
+        self.load_text(url)
+    }
+
+    fn get_bytes(&self, url: &str) -> Result<Response<Vec<u8>>, LodestoneError> {
+        self.load(url)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for ReplayTransport {
+    fn get<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response, LodestoneError>> {
+        Box::pin(async move { self.load_text(url) })
+    }
+
+    fn get_bytes<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Response<Vec<u8>>, LodestoneError>> {
+        Box::pin(async move { self.load(url) })
+    }
+}
+
+fn fixture_path(dir: &Path, url: &str) -> PathBuf {
+    dir.join(file_name(url) + ".html")
+}
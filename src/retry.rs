@@ -0,0 +1,121 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::client::Response;
+use crate::error::LodestoneError;
+
+/// Decides whether, and after how long, a failed request is tried again.
+///
+/// Network errors and the configured status codes, unless turned off,
+/// are retried with an exponential backoff, unless the Lodestone says when to come back
+/// through a `Retry-After` header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    statuses: Vec<u16>,
+    network: bool,
+}
+
+impl Default for RetryPolicy {
+    /// Up to 3 attempts, waiting 500ms and then 1s with jitter, on network
+    /// errors, rate limiting and the errors of an overloaded server.
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            statuses: vec![429, 500, 502, 503, 504],
+            network: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes every request a single attempt.
+    pub fn none() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// How many times a request is made at most, including the first attempt.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// How long to wait before the first retry. Each retry after it waits twice as long.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// The longest time to wait between attempts. Requests the Lodestone
+    /// asks to retry any later than this are given up on instead.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Whether to randomly shorten delays by up to half, so that clients
+    /// which failed together don't all retry at the same time.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Which HTTP status codes are worth retrying.
+    pub fn retry_on(mut self, statuses: &[u16]) -> Self {
+        self.statuses = statuses.to_vec();
+        self
+    }
+
+    /// Whether network errors, such as timeouts or connections
+    /// being reset, are worth retrying.
+    pub fn retry_network(mut self, retry: bool) -> Self {
+        self.network = retry;
+        self
+    }
+
+    /// Returns how long to wait before trying again after the given attempt,
+    /// or `None` if its outcome should be returned as it is.
+    pub(crate) fn delay<B>(&self, attempt: u32, outcome: &Result<Response<B>, LodestoneError>) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        match outcome {
+            Err(LodestoneError::Network(_)) if self.network => Some(self.backoff(attempt)),
+            Ok(response) if self.statuses.contains(&response.status) => match response.retry_after {
+                Some(delay) if delay > self.max_delay => None,
+                Some(delay) => Some(delay),
+                None => Some(self.backoff(attempt)),
+            },
+            _ => None,
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+
+        match self.jitter {
+            true => delay.mul_f64(1.0 - random_fraction() / 2.0),
+            false => delay,
+        }
+    }
+}
+
+/// A random number in `[0, 1)`, which is all the randomness jitter needs.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
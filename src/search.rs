@@ -1,26 +1,372 @@
-use failure::Error;
+use thiserror::Error;
 use select::document::Document;
-use select::predicate::Class;
+use select::node::Node;
+use select::predicate::{Class, Name, Predicate};
 
-use crate::CLIENT;
+use crate::{CLIENT, Lodestone};
+use crate::error::LodestoneError;
 use crate::model::profile::Profile;
+use crate::model::clan::Clan;
+use crate::model::class::ClassType;
 use crate::model::datacenter::Datacenter;
+use crate::model::domain::Domain;
 use crate::model::gc::GrandCompany;
 use crate::model::language::Language;
+use crate::model::race::Race;
+use crate::model::region::Region;
 use crate::model::server::Server;
+use crate::model::util::{ensure_node, HomeInfo};
 
-use std::fmt::Write;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, VecDeque};
+use std::str::FromStr;
 
-static BASE_SEARCH_URL: &str = "https://na.finalfantasyxiv.com/lodestone/character/?";
+use url::Url;
+
+/// A character as listed on the search page.
+///
+/// This only holds what is shown in the listing, which makes searching
+/// a single request. Use `Lodestone::resolve` to get the full profile.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchResult {
+    /// The id associated with the profile
+    pub user_id: u32,
+    /// The character's in-game name.
+    pub name: String,
+    /// Which server the character is in.
+    pub server: Server,
+    /// Which datacenter the character is in.
+    pub datacenter: Datacenter,
+    /// URL of the character's face image.
+    pub face_url: String,
+    /// URL of the icon for the character's grand company rank, if they are in one.
+    pub gc_rank_icon: Option<String>,
+    /// The name of the character's Free Company, if they are in one.
+    pub free_company: Option<String>,
+    /// The language the character has set on their profile, if any.
+    pub language: Option<Language>,
+}
+
+impl SearchResult {
+    /// Fetches the full profile for this character using the default client.
+    ///
+    /// Results of a search made with a configured client should be resolved
+    /// with `Lodestone::resolve` instead, so they go through the same client.
+    pub fn resolve(&self) -> Result<Profile, LodestoneError> {
+        Profile::get(self.user_id)
+    }
+
+    /// Async version of `SearchResult::resolve`, only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn resolve_async(&self) -> Result<Profile, LodestoneError> {
+        Profile::get_async(self.user_id).await
+    }
+
+    fn parse_entry(entry: &Node) -> Result<Self, LodestoneError> {
+        let link = ensure_node!(entry, Class("entry__link"));
+        let home_info = HomeInfo::parse(&ensure_node!(entry, Class("entry__world")).text())?;
+        let face = ensure_node!(entry, Class("entry__chara__face").descendant(Name("img")));
+
+        Ok(SearchResult {
+            user_id: Self::parse_id(link.attr("href").unwrap_or_default())?,
+            name: ensure_node!(entry, Class("entry__name")).text(),
+            server: home_info.server,
+            datacenter: home_info.datacenter,
+            face_url: face.attr("src").unwrap_or_default().to_string(),
+            gc_rank_icon: entry
+                .find(Class("entry__chara_info").descendant(Class("js__tooltip")).descendant(Name("img")))
+                .next()
+                .and_then(|img| img.attr("src"))
+                .map(|src| src.to_string()),
+            free_company: entry
+                .find(Class("entry__freecompany__link").descendant(Name("span")))
+                .next()
+                .map(|node| node.text()),
+            language: entry
+                .find(Class("entry__chara__lang"))
+                .next()
+                .and_then(|node| Language::from_str(node.text().trim()).ok()),
+        })
+    }
+
+    /// Pulls the user id out of a link to a profile.
+    fn parse_id(href: &str) -> Result<u32, LodestoneError> {
+        let digits = href.chars()
+            .skip_while(|ch| !ch.is_ascii_digit())
+            .take_while(|ch| ch.is_ascii_digit())
+            .collect::<String>();
+
+        Ok(digits.parse::<u32>()?)
+    }
+}
+
+/// A single page of search results.
+#[derive(Debug)]
+pub struct SearchPage {
+    /// The characters listed on this page.
+    pub results: Vec<SearchResult>,
+    /// Why the entries which are missing from `results` couldn't be
+    /// parsed, e.g. because they live on a server that isn't known yet.
+    pub errors: Vec<LodestoneError>,
+    /// The number of this page, starting at 1.
+    pub page: u32,
+    /// How many pages of results there are in total.
+    pub total_pages: u32,
+    /// How many characters matched the search in total.
+    pub total_results: u32,
+}
+
+impl SearchPage {
+    /// Whether there are more pages after this one.
+    pub fn has_next(&self) -> bool {
+        self.page < self.total_pages
+    }
+
+    /// Combines the same page from searches over several datacenters.
+    pub(crate) fn merge(page: u32, pages: Vec<SearchPage>) -> Self {
+        if pages.len() == 1 {
+            return pages.into_iter().next().unwrap();
+        }
+
+        let mut merged = SearchPage {
+            page,
+            total_pages: pages.iter().map(|p| p.total_pages).max().unwrap_or(1),
+            total_results: pages.iter().map(|p| p.total_results).sum(),
+            results: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        for page in pages {
+            merged.results.extend(page.results);
+            merged.errors.extend(page.errors);
+        }
+
+        merged
+    }
+
+    /// Parses a search page, including the pager telling which page this is.
+    ///
+    /// Entries are parsed one by one, so that one which can't be
+    /// parsed ends up in `errors` without losing the others.
+    pub(crate) fn parse(html: &str) -> Result<Self, LodestoneError> {
+        let doc = Document::from(html);
+
+        let (mut results, mut errors) = (Vec::new(), Vec::new());
+        let entries = doc.find(Class("entry"))
+            .filter(|entry| entry.find(Class("entry__link")).next().is_some());
+
+        for entry in entries {
+            match SearchResult::parse_entry(&entry) {
+                Ok(result) => results.push(result),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        //  The total comes as e.g. "123 Total" and the pager as "Page 1 of 3",
+        //  so only the numbers are looked at to not depend on the language.
+        let total_results = doc.find(Class("parts__total"))
+            .next()
+            .and_then(|node| parse_numbers(&node.text()).first().copied())
+            .unwrap_or(results.len() as u32);
+
+        let pager = doc.find(Class("btn__pager__current"))
+            .next()
+            .map(|node| parse_numbers(&node.text()))
+            .unwrap_or_default();
+
+        let (page, total_pages) = match pager[..] {
+            [page, total_pages, ..] => (page, total_pages),
+            _ => (1, 1),
+        };
+
+        Ok(SearchPage {
+            results,
+            errors,
+            page,
+            total_pages,
+            total_results,
+        })
+    }
+}
+
+/// Lazily walks through every page of a search, only fetching
+/// the next page once all results from the previous one were taken.
+///
+/// When searching a region, every page of its first datacenter is
+/// walked through before moving on to the next datacenter.
+///
+/// Entries which can't be parsed come out as errors after the rest of
+/// their page, and iteration goes on. It stops after the first page
+/// which can't be fetched.
+pub struct SearchIter<'a> {
+    client: &'a Lodestone,
+    searches: VecDeque<SearchBuilder>,
+    results: std::vec::IntoIter<Result<SearchResult, LodestoneError>>,
+    next_page: Option<u32>,
+}
+
+impl<'a> SearchIter<'a> {
+    pub(crate) fn new(client: &'a Lodestone, search: SearchBuilder) -> Self {
+        let next_page = Some(search.page.unwrap_or(1));
+
+        SearchIter {
+            client,
+            searches: search.split_region().into(),
+            results: Vec::new().into_iter(),
+            next_page,
+        }
+    }
+}
+
+impl Iterator for SearchIter<'_> {
+    type Item = Result<SearchResult, LodestoneError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.results.next() {
+                return Some(result);
+            }
+
+            //  Once a search runs out of pages, move on to the next one
+            let page = match self.next_page {
+                Some(page) => page,
+                None => {
+                    self.searches.pop_front();
+                    self.searches.front()?.page.unwrap_or(1)
+                },
+            };
+
+            let search = self.searches.front()?.clone().page(page);
+
+            match self.client.search_page(search) {
+                Ok(search_page) => {
+                    self.next_page = if search_page.has_next() {
+                        Some(search_page.page + 1)
+                    } else {
+                        None
+                    };
+                    self.results = search_page.results
+                        .into_iter()
+                        .map(Ok)
+                        .chain(search_page.errors.into_iter().map(Err))
+                        .collect::<Vec<_>>()
+                        .into_iter();
+                },
+                Err(e) => {
+                    self.searches.clear();
+                    self.next_page = None;
+                    return Some(Err(e));
+                },
+            }
+        }
+    }
+}
+
+/// Gets every number in a string, ignoring thousands separators.
+fn parse_numbers(text: &str) -> Vec<u32> {
+    text.split(|ch: char| !ch.is_ascii_digit() && ch != ',' && ch != '.')
+        .map(|part| part.replace([',', '.'], ""))
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Represents ways in which the criteria of a search can be invalid.
+#[derive(Clone, Debug, Eq, PartialEq, Error)]
+pub enum QueryError {
+    /// The character name to search for is empty.
+    #[error("Character name is empty")]
+    EmptyName,
+    /// The character name is longer than a name can be in game.
+    #[error("Character name '{0}' is too long")]
+    NameTooLong(String),
+    /// The character name has more than a forename and a surname.
+    #[error("Character name '{0}' has too many parts")]
+    TooManyNameParts(String),
+    /// The character name contains a character names can't have.
+    #[error("Character name '{0}' contains invalid character '{1}'")]
+    InvalidCharacter(String, char),
+    /// Pages start at 1.
+    #[error("Page numbers start at 1")]
+    InvalidPage,
+}
+
+/// The longest a forename or surname can be in game.
+const MAX_NAME_PART_LEN: usize = 15;
+/// The longest a full name can be in game, not counting the space.
+const MAX_NAME_LEN: usize = 20;
+
+/// Checks a name, or part of one, against the in-game naming rules.
+/// Names are made of a forename and a surname, each of which only
+/// contains letters, apostrophes and hyphens.
+fn validate_name(name: &str) -> Result<(), QueryError> {
+    let parts = name.split_whitespace().collect::<Vec<_>>();
+
+    if parts.is_empty() {
+        return Err(QueryError::EmptyName);
+    }
+
+    if parts.len() > 2 {
+        return Err(QueryError::TooManyNameParts(name.into()));
+    }
+
+    if let Some(ch) = parts.iter()
+        .flat_map(|part| part.chars())
+        .find(|ch| !ch.is_alphabetic() && *ch != '\'' && *ch != '’' && *ch != '-') {
+        return Err(QueryError::InvalidCharacter(name.into(), ch));
+    }
+
+    let lengths = parts.iter().map(|part| part.chars().count());
+
+    if lengths.clone().any(|len| len > MAX_NAME_PART_LEN) || lengths.sum::<usize>() > MAX_NAME_LEN {
+        return Err(QueryError::NameTooLong(name.into()));
+    }
+
+    Ok(())
+}
+
+/// The orders search results can be sorted in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum SortOrder {
+    NameAscending,
+    NameDescending,
+    WorldAscending,
+    WorldDescending,
+    LevelDescending,
+    LevelAscending,
+}
+
+impl SortOrder {
+    /// The value used for this order in Lodestone URLs.
+    fn id(self) -> u32 {
+        match self {
+            SortOrder::NameAscending => 1,
+            SortOrder::NameDescending => 2,
+            SortOrder::WorldAscending => 3,
+            SortOrder::WorldDescending => 4,
+            SortOrder::LevelDescending => 5,
+            SortOrder::LevelAscending => 6,
+        }
+    }
+}
+
+/// Whether to filter by a whole race, or only one of its clans.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum RaceFilter {
+    Race(Race),
+    Clan(Clan),
+}
 
 #[derive(Clone, Debug, Default)]
 pub struct SearchBuilder {
+    pub(crate) page: Option<u32>,
     server: Option<Server>,
     datacenter: Option<Datacenter>,
+    region: Option<Region>,
     character: Option<String>,
-    lang: HashSet<Language>,
-    gc: HashSet<GrandCompany>,
+    lang: BTreeSet<Language>,
+    gc: BTreeSet<GrandCompany>,
+    class: Option<ClassType>,
+    race: Option<RaceFilter>,
+    order: Option<SortOrder>,
 }
 
 impl SearchBuilder {
@@ -30,64 +376,133 @@ impl SearchBuilder {
         }
     }
 
-    /// Builds the search and executes it, returning a list of profiles
+    /// Builds the search and executes it, returning a list of characters
     /// that match the given criteria.
-    pub fn send(self) -> Result<Vec<Profile>, Error> {
-        let mut url = BASE_SEARCH_URL.to_owned();
+    ///
+    /// Only a single page of results is returned, see `SearchBuilder::iter`
+    /// to go through all of them.
+    pub fn send(self) -> Result<Vec<SearchResult>, LodestoneError> {
+        CLIENT.search(self)
+    }
+
+    /// Same as `SearchBuilder::send`, but also returns the pagination info.
+    pub fn send_page(self) -> Result<SearchPage, LodestoneError> {
+        CLIENT.search_page(self)
+    }
+
+    /// Returns an iterator over the results on every page, starting
+    /// from the page set with `SearchBuilder::page` if any.
+    pub fn iter(self) -> SearchIter<'static> {
+        CLIENT.search_iter(self)
+    }
+
+    /// Async version of `SearchBuilder::send`, only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn send_async(self) -> Result<Vec<SearchResult>, LodestoneError> {
+        CLIENT.search_async(self).await
+    }
 
-        if let Some(name) = self.character {
-            let _ = write!(url, "q={}&", name);
+    /// Async version of `SearchBuilder::send_page`, only available with the `async` feature.
+    #[cfg(feature = "async")]
+    pub async fn send_page_async(self) -> Result<SearchPage, LodestoneError> {
+        CLIENT.search_page_async(self).await
+    }
+
+    /// Splits a search over a region into one search per datacenter.
+    pub(crate) fn split_region(&self) -> Vec<SearchBuilder> {
+        match self.region {
+            Some(region) => region
+                .datacenters()
+                .iter()
+                .map(|dc| self.clone().datacenter(*dc))
+                .collect(),
+            None => vec![self.clone()],
         }
+    }
 
-        if let Some(dc) = self.datacenter {
-            let _ = write!(url, "worldname=_dc_{}&", dc);
+    /// Checks that the search criteria are valid, which is
+    /// also done before a search is sent.
+    pub fn validate(&self) -> Result<(), QueryError> {
+        if let Some(name) = &self.character {
+            validate_name(name)?;
         }
 
-        if let Some(s) = self.server {
-            let _ = write!(url, "worldname={}&", s);
+        if self.page == Some(0) {
+            return Err(QueryError::InvalidPage);
         }
 
-        self.lang.iter().for_each(|lang| {
-            let _ = match lang {
-                Language::Japanese => write!(url, "blog_lang=ja&"),
-                Language::English => write!(url, "blog_lang=en&"),
-                Language::German => write!(url, "blog_lang=de&"),
-                Language::French => write!(url, "blog_lang=fr&"),
-            };
-        });
-
-        self.gc.iter().for_each(|gc| {        
-            let _ = match gc {
-                GrandCompany::Unaffiliated => write!(url, "gcid=0&"),
-                GrandCompany::Maelstrom => write!(url, "gcid=1&"),
-                GrandCompany::TwinAdder => write!(url, "gcid=2&"),
-                GrandCompany::ImmortalFlames => write!(url, "gcid=3&"),
-            };
-        });
+        Ok(())
+    }
+
+    /// Creates the search URL on the given Lodestone from the given criteria.
+    pub(crate) fn build_url(&self, domain: Domain) -> Result<Url, LodestoneError> {
+        self.validate()?;
 
-        let url = url.trim_end_matches('&');
+        let mut url = Url::parse(&format!("{}character/", domain.base_url()))?;
 
-        let response = CLIENT.get(url).send()?;
-        let text = response.text()?;
-        let doc = Document::from(text.as_str());
+        {
+            let mut query = url.query_pairs_mut();
 
-        Ok(doc.find(Class("entry__link"))
-            .filter_map(|node| node
-                .attr("href")
-                .and_then(|text| {
-                    let digits = text.chars()
-                        .skip_while(|ch| !ch.is_digit(10))
-                        .take_while(|ch| ch.is_digit(10))
-                        .collect::<String>();
-                    
-                    digits.parse::<u32>().ok()
-                })
-                .and_then(|id| {
-                    let profile = Profile::get(id);
+            if let Some(name) = &self.character {
+                query.append_pair("q", name.trim());
+            }
 
-                    profile.ok()
-                }))
-            .collect())
+            if let Some(dc) = self.datacenter {
+                query.append_pair("worldname", &format!("_dc_{}", dc));
+            }
+
+            if let Some(s) = self.server {
+                query.append_pair("worldname", &s.to_string());
+            }
+
+            self.lang.iter().for_each(|lang| {
+                let lang = match lang {
+                    Language::Japanese => "ja",
+                    Language::English => "en",
+                    Language::German => "de",
+                    Language::French => "fr",
+                };
+                query.append_pair("blog_lang", lang);
+            });
+
+            self.gc.iter().for_each(|gc| {
+                let gcid = match gc {
+                    GrandCompany::Unaffiliated => "0",
+                    GrandCompany::Maelstrom => "1",
+                    GrandCompany::TwinAdder => "2",
+                    GrandCompany::ImmortalFlames => "3",
+                };
+                query.append_pair("gcid", gcid);
+            });
+
+            if let Some(class) = self.class {
+                query.append_pair("classjob", &class.id().to_string());
+            }
+
+            if let Some(race) = self.race {
+                let race_tribe = match race {
+                    RaceFilter::Race(race) => format!("race_{}", race.id()),
+                    RaceFilter::Clan(clan) => format!("tribe_{}", clan.id()),
+                };
+                query.append_pair("race_tribe", &race_tribe);
+            }
+
+            if let Some(order) = self.order {
+                query.append_pair("order", &order.id().to_string());
+            }
+
+            if let Some(page) = self.page {
+                query.append_pair("page", &page.to_string());
+            }
+        }
+
+        Ok(url)
+    }
+
+    /// Which page of results to get, starting at 1.
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = Some(page);
+        self
     }
 
     /// A character name to search for. This can only be called once,
@@ -103,6 +518,7 @@ impl SearchBuilder {
     pub fn datacenter<D: Into<Datacenter>>(mut self, datacenter: D) -> Self {
         self.datacenter = Some(datacenter.into());
         self.server = None;
+        self.region = None;
         self
     }
 
@@ -112,6 +528,19 @@ impl SearchBuilder {
     pub fn server<S: Into<Server>>(mut self, server: S) -> Self {
         self.server = Some(server.into());
         self.datacenter = None;
+        self.region = None;
+        self
+    }
+
+    /// A region to search in. Mutually exclusive to server and datacenter,
+    /// replacing them if either was specified before calling this method.
+    ///
+    /// The Lodestone can't search a whole region at once, so this sends
+    /// one search per datacenter in the region and combines the results.
+    pub fn region<R: Into<Region>>(mut self, region: R) -> Self {
+        self.region = Some(region.into());
+        self.server = None;
+        self.datacenter = None;
         self
     }
 
@@ -128,4 +557,33 @@ impl SearchBuilder {
         self.gc.insert(gc.into());
         self
     }
+
+    /// Which class or job to filter by. This can only be called once,
+    /// and any further calls will simply overwrite the previous class.
+    pub fn class<C: Into<ClassType>>(mut self, class: C) -> Self {
+        self.class = Some(class.into());
+        self
+    }
+
+    /// Which race to filter by. Mutually exclusive to clan.
+    /// If a clan was specified before calling this method,
+    /// it will be replaced by the newer race.
+    pub fn race<R: Into<Race>>(mut self, race: R) -> Self {
+        self.race = Some(RaceFilter::Race(race.into()));
+        self
+    }
+
+    /// Which clan to filter by. Mutually exclusive to race.
+    /// If a race was specified before calling this method,
+    /// it will be replaced by the newer clan.
+    pub fn clan<C: Into<Clan>>(mut self, clan: C) -> Self {
+        self.race = Some(RaceFilter::Clan(clan.into()));
+        self
+    }
+
+    /// The order to sort results in.
+    pub fn order(mut self, order: SortOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
 }
\ No newline at end of file